@@ -1,6 +1,6 @@
 use std::ffi::{c_long, c_uchar, c_void, CString};
 
-use hotsync_conduit_rs::{ConduitBuilder, ConduitDBSource};
+use hotsync_conduit_rs::{CSyncProperties, ConduitBuilder, ConduitDBSource};
 use palmrs::database::{PalmDatabase, PdbDatabase};
 
 const CREATOR: [c_uchar; 4] = [b'H', b'E', b'F', b'f'];
@@ -8,11 +8,15 @@ const AUTHOR_DB: &[u8] = include_bytes!("HeffalumpAuthorDB.pdb");
 const CONTENT_DB: &[u8] = include_bytes!("HeffalumpContentDB.pdb");
 
 #[no_mangle]
-pub extern "cdecl" fn OpenConduit(_: *const c_void, _: *const c_void) -> c_long {
+pub unsafe extern "cdecl" fn OpenConduit(
+    _: *const c_void,
+    sync_props: *const CSyncProperties,
+) -> c_long {
     let conduit = ConduitBuilder::<Vec<u8>>::new_with_name_creator(
         CString::new("heffalump_conduit").unwrap(),
         CREATOR,
     )
+    .with_sync_properties(&*sync_props)
     .overwrite_db(ConduitDBSource::Static(
         CString::new("HeffalumpAuthorDB").unwrap(),
         [b'A', b'u', b't', b'h'],
@@ -0,0 +1,137 @@
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use crate::{database_info::DatabaseInfo, pdb_file::file_name, syncmgr_extern::eDbFlags};
+
+const MANIFEST_NAME: &str = "backup.manifest";
+
+/// Which databases to install when restoring from a backup folder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestoreOptions {
+    /// Skip databases that have the same name as one in ROM
+    pub skip_rom_names: bool,
+    /// Leave databases already on the handheld alone, instead of replacing them
+    pub only_missing: bool,
+    /// The memory card to install to
+    pub card_no: u8,
+}
+
+impl Default for RestoreOptions {
+    fn default() -> Self {
+        Self {
+            skip_rom_names: true,
+            only_missing: false,
+            card_no: 0,
+        }
+    }
+}
+
+/// The state each database was in when it was last backed up, keyed by file name
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct BackupManifest {
+    dir: PathBuf,
+    backed_up: HashMap<String, (u32, bool)>,
+}
+
+impl BackupManifest {
+    /// Load the manifest for a backup folder, starting empty if there hasn't been a backup yet
+    pub(crate) fn load(dir: &Path) -> Result<Self, Error> {
+        let contents = match std::fs::read_to_string(dir.join(MANIFEST_NAME)) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let invalid = || Error::new(ErrorKind::InvalidData, "Invalid backup manifest");
+        let mut backed_up = HashMap::new();
+        for line in contents.lines().filter(|line| !line.is_empty()) {
+            let mut fields = line.splitn(3, '\t');
+            let (Some(mod_number), Some(backup_flag), Some(name)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid());
+            };
+            let mod_number = mod_number.parse().map_err(|_| invalid())?;
+            backed_up.insert(name.to_string(), (mod_number, backup_flag == "1"));
+        }
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            backed_up,
+        })
+    }
+
+    pub(crate) fn save(&self) -> Result<(), Error> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut names: Vec<_> = self.backed_up.keys().collect();
+        names.sort();
+        let contents: String = names
+            .into_iter()
+            .map(|name| {
+                let (mod_number, backup_flag) = self.backed_up[name];
+                format!("{mod_number}\t{}\t{name}\n", u8::from(backup_flag))
+            })
+            .collect();
+        std::fs::write(self.dir.join(MANIFEST_NAME), contents)
+    }
+
+    /// Whether the database changed since its last backup, or its backup file has gone missing
+    pub(crate) fn needs_backup(&self, info: &DatabaseInfo) -> bool {
+        let name = Self::file_name(info);
+        self.backed_up.get(&name) != Some(&Self::state(info)) || !self.dir.join(name).exists()
+    }
+
+    pub(crate) fn record(&mut self, info: &DatabaseInfo) {
+        self.backed_up
+            .insert(Self::file_name(info), Self::state(info));
+    }
+
+    fn file_name(info: &DatabaseInfo) -> String {
+        file_name(&info.name, info.flags.contains(eDbFlags::eResource))
+    }
+
+    fn state(info: &DatabaseInfo) -> (u32, bool) {
+        (info.mod_number, info.flags.contains(eDbFlags::eBackupDb))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::CString;
+
+    use super::*;
+
+    #[test]
+    fn test_manifest() {
+        let dir = std::env::temp_dir().join(format!("hs_backup_test_{}", std::process::id()));
+        let mut info = DatabaseInfo {
+            name: CString::new("HeffalumpAuthorDB").unwrap(),
+            card_no: 0,
+            flags: eDbFlags::eBackupDb,
+            misc_flags: 0,
+            db_type: *b"DATA",
+            creator: *b"Test",
+            version: 0,
+            mod_number: 3,
+            index: 0,
+            create_date: None,
+            mod_date: None,
+            backup_date: None,
+        };
+
+        let mut manifest = BackupManifest::load(&dir).unwrap();
+        assert!(manifest.needs_backup(&info));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("HeffalumpAuthorDB.pdb"), b"").unwrap();
+        manifest.record(&info);
+        manifest.save().unwrap();
+
+        let manifest = BackupManifest::load(&dir).unwrap();
+        assert!(!manifest.needs_backup(&info));
+        info.mod_number += 1;
+        assert!(manifest.needs_backup(&info));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+use crate::{error::ConduitError, syncmgr_extern::MAX_RECORD_SIZE};
+
+/// Marks a record as one chunk of a blob
+const CHUNK_MAGIC: [u8; 4] = *b"BLOB";
+/// Magic, blob ID, chunk index, chunk count, total length, CRC-32 of the whole blob
+const CHUNK_HEADER_LEN: usize = 4 + 2 + 2 + 2 + 4 + 4;
+/// Payload bytes carried by each chunk record
+const CHUNK_DATA_LEN: usize = MAX_RECORD_SIZE - CHUNK_HEADER_LEN;
+
+/// The chunks of one blob read so far, along with the header they must all agree on
+struct PartialBlob {
+    chunk_count: u16,
+    total_len: u32,
+    crc: u32,
+    chunks: BTreeMap<u16, Vec<u8>>,
+}
+
+/// Split a blob into chunk records, each small enough to write to the handheld. An empty blob
+/// still gets one chunk so it can be found again
+pub(crate) fn split_blob(blob_id: u16, data: &[u8]) -> Result<Vec<Vec<u8>>, ConduitError> {
+    let chunk_count = data.len().div_ceil(CHUNK_DATA_LEN).max(1);
+    let (Ok(chunk_count), Ok(total_len)) = (u16::try_from(chunk_count), u32::try_from(data.len()))
+    else {
+        return Err(ConduitError::InvalidBlob);
+    };
+    let crc = crc32(data);
+    Ok((0..chunk_count)
+        .map(|index| {
+            let start = index as usize * CHUNK_DATA_LEN;
+            let chunk = &data[start..(start + CHUNK_DATA_LEN).min(data.len())];
+            let mut record = Vec::with_capacity(CHUNK_HEADER_LEN + chunk.len());
+            record.extend_from_slice(&CHUNK_MAGIC);
+            record.extend_from_slice(&blob_id.to_be_bytes());
+            record.extend_from_slice(&index.to_be_bytes());
+            record.extend_from_slice(&chunk_count.to_be_bytes());
+            record.extend_from_slice(&total_len.to_be_bytes());
+            record.extend_from_slice(&crc.to_be_bytes());
+            record.extend_from_slice(chunk);
+            record
+        })
+        .collect())
+}
+
+/// Reassemble every blob from its chunk records, in any order, checking that no chunk is
+/// missing and that each blob matches its checksum
+pub(crate) fn join_blobs(
+    records: impl IntoIterator<Item = Vec<u8>>,
+) -> Result<BTreeMap<u16, Vec<u8>>, ConduitError> {
+    let mut blobs: BTreeMap<u16, PartialBlob> = BTreeMap::new();
+    for mut record in records {
+        if record.len() < CHUNK_HEADER_LEN || record[..4] != CHUNK_MAGIC {
+            return Err(ConduitError::InvalidBlob);
+        }
+        let read_u16 = |at: usize| u16::from_be_bytes([record[at], record[at + 1]]);
+        let read_u32 = |at: usize| u32::from_be_bytes(record[at..at + 4].try_into().unwrap());
+        let (blob_id, index, chunk_count) = (read_u16(4), read_u16(6), read_u16(8));
+        let (total_len, crc) = (read_u32(10), read_u32(14));
+        let data = record.split_off(CHUNK_HEADER_LEN);
+
+        let blob = blobs.entry(blob_id).or_insert_with(|| PartialBlob {
+            chunk_count,
+            total_len,
+            crc,
+            chunks: BTreeMap::new(),
+        });
+        if (blob.chunk_count, blob.total_len, blob.crc) != (chunk_count, total_len, crc)
+            || index >= chunk_count
+            || blob.chunks.insert(index, data).is_some()
+        {
+            return Err(ConduitError::InvalidBlob);
+        }
+    }
+
+    blobs
+        .into_iter()
+        .map(|(blob_id, blob)| {
+            if blob.chunks.len() != blob.chunk_count as usize {
+                return Err(ConduitError::InvalidBlob);
+            }
+            let data: Vec<u8> = blob.chunks.into_values().flatten().collect();
+            if data.len() != blob.total_len as usize || crc32(&data) != blob.crc {
+                return Err(ConduitError::InvalidBlob);
+            }
+            Ok((blob_id, data))
+        })
+        .collect()
+}
+
+/// CRC-32 as used by zip and PNG
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_blob_round_trip() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let big: Vec<u8> = (0..CHUNK_DATA_LEN * 2 + 10).map(|i| i as u8).collect();
+        let mut records = split_blob(7, &big).unwrap();
+        assert_eq!(records.len(), 3);
+        assert!(records.iter().all(|record| record.len() <= MAX_RECORD_SIZE));
+        records.extend(split_blob(1, b"").unwrap());
+        // the handheld may hand chunks back in any order
+        records.reverse();
+
+        let blobs = join_blobs(records.clone()).unwrap();
+        assert_eq!(blobs.len(), 2);
+        assert_eq!(blobs[&7], big);
+        assert_eq!(blobs[&1], b"");
+
+        let mut corrupt = records.clone();
+        *corrupt[1].last_mut().unwrap() ^= 0xFF;
+        assert!(matches!(
+            join_blobs(corrupt),
+            Err(ConduitError::InvalidBlob)
+        ));
+        records.remove(1);
+        assert!(matches!(
+            join_blobs(records),
+            Err(ConduitError::InvalidBlob)
+        ));
+    }
+}
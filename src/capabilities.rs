@@ -0,0 +1,91 @@
+use crate::{device_info::RomVersion, error::ConduitError};
+
+/// A Sync Manager API version, as (major, minor)
+pub type ApiVersion = (u32, u32);
+
+/// Something not every Sync Manager supports, along with the API version that added it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Reading part of a record or block into a small buffer to learn its full size. Without
+    /// it, reads use a buffer large enough for any record
+    PartialReads,
+    /// Calling a module on the handheld with `SyncCallRemoteModule`
+    RemoteModules,
+    /// Reading handheld features with `SyncReadFeature`
+    Features,
+    /// Reading the handheld OS version with `SyncGetHHOSVersion`
+    OsVersion,
+    /// Finding databases and reading the info of an open database
+    FindDatabases,
+}
+
+impl Capability {
+    /// The first Sync Manager API version with this capability
+    pub fn min_api_version(self) -> ApiVersion {
+        match self {
+            Capability::PartialReads
+            | Capability::RemoteModules
+            | Capability::Features
+            | Capability::OsVersion => (2, 1),
+            Capability::FindDatabases => (2, 2),
+        }
+    }
+}
+
+/// What the Sync Manager and handheld support, read once when the session starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub api_version: ApiVersion,
+    /// `None` if the Sync Manager can't report it
+    pub os_version: Option<RomVersion>,
+    /// Whether the Sync Manager DLL exports the database find and info calls, which can't be
+    /// used whatever version it reports otherwise
+    pub has_find_calls: bool,
+}
+
+impl Capabilities {
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.api_version >= capability.min_api_version()
+            && (capability != Capability::FindDatabases || self.has_find_calls)
+    }
+
+    /// Refuse with `ConduitError::Unsupported` unless the capability is available
+    pub fn require(&self, capability: Capability) -> Result<(), ConduitError> {
+        if self.supports(capability) {
+            Ok(())
+        } else {
+            Err(ConduitError::Unsupported(capability, self.api_version))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_require() {
+        let caps = Capabilities {
+            api_version: (2, 1),
+            os_version: None,
+            has_find_calls: true,
+        };
+        assert!(caps.supports(Capability::PartialReads));
+        assert!(caps.require(Capability::RemoteModules).is_ok());
+        assert!(matches!(
+            caps.require(Capability::FindDatabases),
+            Err(ConduitError::Unsupported(Capability::FindDatabases, (2, 1)))
+        ));
+        let caps = Capabilities {
+            api_version: (3, 0),
+            ..caps
+        };
+        assert!(caps.supports(Capability::FindDatabases));
+        let caps = Capabilities {
+            has_find_calls: false,
+            ..caps
+        };
+        assert!(!caps.supports(Capability::FindDatabases));
+        assert!(caps.supports(Capability::PartialReads));
+    }
+}
@@ -0,0 +1,217 @@
+use std::ffi::CString;
+
+use crate::error::ConduitError;
+
+/// Number of categories in the standard AppInfo block, including Unfiled
+pub const CATEGORY_COUNT: usize = 16;
+/// Length of a category name, including the terminating nul
+const CATEGORY_NAME_LEN: usize = 16;
+/// Size of the standard category block at the start of the AppInfo block
+const CATEGORY_BLOCK_LEN: usize = 2 + CATEGORY_COUNT * CATEGORY_NAME_LEN + CATEGORY_COUNT + 2;
+/// Unique IDs below this are assigned by the handheld, the rest by the desktop
+const DESKTOP_FIRST_ID: u8 = 128;
+
+/// The standard category layout most applications keep at the start of their AppInfo block
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryAppInfo {
+    /// One bit per category, set when the category was renamed since the last sync
+    pub renamed: u16,
+    /// Category names. An empty name marks an unused slot; slot 0 is Unfiled
+    pub names: [CString; CATEGORY_COUNT],
+    pub ids: [u8; CATEGORY_COUNT],
+    pub last_unique_id: u8,
+    /// Application-specific data that follows the category block
+    pub app_data: Vec<u8>,
+}
+
+impl CategoryAppInfo {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConduitError> {
+        if bytes.len() < CATEGORY_BLOCK_LEN {
+            return Err(ConduitError::InvalidAppInfo);
+        }
+        let renamed = u16::from_be_bytes([bytes[0], bytes[1]]);
+        let names_end = 2 + CATEGORY_COUNT * CATEGORY_NAME_LEN;
+        let names = std::array::from_fn(|idx| {
+            let name = &bytes[2 + idx * CATEGORY_NAME_LEN..2 + (idx + 1) * CATEGORY_NAME_LEN];
+            let len = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+            CString::new(&name[..len]).unwrap()
+        });
+        let ids = bytes[names_end..names_end + CATEGORY_COUNT]
+            .try_into()
+            .unwrap();
+        Ok(Self {
+            renamed,
+            names,
+            ids,
+            last_unique_id: bytes[names_end + CATEGORY_COUNT],
+            app_data: bytes[CATEGORY_BLOCK_LEN..].to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(CATEGORY_BLOCK_LEN + self.app_data.len());
+        bytes.extend_from_slice(&self.renamed.to_be_bytes());
+        for name in &self.names {
+            let mut padded = [0_u8; CATEGORY_NAME_LEN];
+            let name = name.as_bytes();
+            let len = name.len().min(CATEGORY_NAME_LEN - 1);
+            padded[..len].copy_from_slice(&name[..len]);
+            bytes.extend_from_slice(&padded);
+        }
+        bytes.extend_from_slice(&self.ids);
+        bytes.push(self.last_unique_id);
+        // padding
+        bytes.push(0);
+        bytes.extend_from_slice(&self.app_data);
+        bytes
+    }
+
+    /// The index of the category with this name, which is also the category stored in the
+    /// attributes of its records
+    pub fn index_of(&self, name: &CString) -> Option<u8> {
+        self.names
+            .iter()
+            .position(|existing| !existing.is_empty() && existing == name)
+            .map(|idx| idx as u8)
+    }
+
+    fn existing_index(&self, name: &CString) -> Result<u8, ConduitError> {
+        self.index_of(name).ok_or(ConduitError::NoSuchCategory)
+    }
+
+    /// Check that a name fits in a category slot and can't be mistaken for an unused one
+    fn check_name(name: &CString) -> Result<(), ConduitError> {
+        match name.as_bytes().len() {
+            0 => Err(ConduitError::EmptyCategoryName),
+            len if len >= CATEGORY_NAME_LEN => Err(ConduitError::CategoryNameTooLong(len)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Rename a category, returning its index. Fails if another category already has the new name
+    pub fn rename(&mut self, from: &CString, to: CString) -> Result<u8, ConduitError> {
+        let idx = self.existing_index(from)?;
+        Self::check_name(&to)?;
+        if self.index_of(&to).is_some_and(|existing| existing != idx) {
+            return Err(ConduitError::DuplicateCategory);
+        }
+        self.names[idx as usize] = to;
+        self.renamed |= 1 << idx;
+        Ok(idx)
+    }
+
+    /// Add a category in the first unused slot, returning its index. A category that already
+    /// exists is left as is
+    pub fn add(&mut self, name: CString) -> Result<u8, ConduitError> {
+        Self::check_name(&name)?;
+        if let Some(idx) = self.index_of(&name) {
+            return Ok(idx);
+        }
+        let idx = (1..CATEGORY_COUNT)
+            .find(|idx| self.names[*idx].is_empty())
+            .ok_or(ConduitError::CategoriesFull)?;
+        let id = (DESKTOP_FIRST_ID..=u8::MAX)
+            .find(|id| !self.ids.contains(id))
+            .ok_or(ConduitError::CategoriesFull)?;
+        self.names[idx] = name;
+        self.ids[idx] = id;
+        self.renamed |= 1 << idx;
+        Ok(idx as u8)
+    }
+
+    /// Free the slot used by a category. Its records need to be moved or purged separately
+    pub fn remove(&mut self, name: &CString) -> Result<u8, ConduitError> {
+        let idx = self.existing_index(name)?;
+        if idx == 0 {
+            // Unfiled always exists
+            return Err(ConduitError::CannotRemoveUnfiled);
+        }
+        self.names[idx as usize] = CString::default();
+        self.ids[idx as usize] = 0;
+        self.renamed &= !(1 << idx);
+        Ok(idx)
+    }
+}
+
+/// A change to the categories of a database on the handheld, identified by name
+#[derive(Debug, Clone, PartialEq)]
+pub enum CategoryEdit {
+    Add(CString),
+    /// Rename the first category to the second
+    Rename(CString, CString),
+    /// Move every record in the first category into the second, then remove the first. The two
+    /// categories must differ
+    Merge(CString, CString),
+    /// Remove a category, moving its records to Unfiled
+    Delete(CString),
+    /// Remove a category along with all of its records
+    Purge(CString),
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_category_round_trip_and_edits() {
+        let mut bytes = vec![0_u8; CATEGORY_BLOCK_LEN];
+        bytes[2..9].copy_from_slice(b"Unfiled");
+        bytes[18..26].copy_from_slice(b"Business");
+        bytes[2 + CATEGORY_COUNT * CATEGORY_NAME_LEN + 1] = 1;
+        bytes[2 + CATEGORY_COUNT * CATEGORY_NAME_LEN + CATEGORY_COUNT] = 1;
+        bytes.extend_from_slice(b"app data");
+
+        let mut info = CategoryAppInfo::from_bytes(&bytes).unwrap();
+        assert_eq!(info.to_bytes(), bytes);
+        assert_eq!(info.app_data, b"app data");
+
+        let business = CString::new("Business").unwrap();
+        assert_eq!(info.index_of(&business), Some(1));
+        assert_eq!(info.add(CString::new("Personal").unwrap()).unwrap(), 2);
+        assert_eq!(info.ids[2], DESKTOP_FIRST_ID);
+        assert_eq!(
+            info.rename(&business, CString::new("Work").unwrap())
+                .unwrap(),
+            1
+        );
+        assert_eq!(info.renamed, 0b110);
+        assert_eq!(info.remove(&CString::new("Work").unwrap()).unwrap(), 1);
+        assert_eq!(info.index_of(&business), None);
+        assert_eq!(info.renamed, 0b100);
+        assert!(matches!(
+            info.remove(&CString::new("Unfiled").unwrap()),
+            Err(ConduitError::CannotRemoveUnfiled)
+        ));
+    }
+
+    #[test]
+    fn test_category_name_checks() {
+        let mut bytes = vec![0_u8; CATEGORY_BLOCK_LEN];
+        bytes[2..9].copy_from_slice(b"Unfiled");
+        bytes[18..26].copy_from_slice(b"Business");
+        let mut info = CategoryAppInfo::from_bytes(&bytes).unwrap();
+        let business = CString::new("Business").unwrap();
+
+        assert!(matches!(
+            info.add(CString::default()),
+            Err(ConduitError::EmptyCategoryName)
+        ));
+        assert!(matches!(
+            info.add(CString::new("Sixteen bytes!!!").unwrap()),
+            Err(ConduitError::CategoryNameTooLong(16))
+        ));
+        assert!(matches!(
+            info.rename(&business, CString::new("Unfiled").unwrap()),
+            Err(ConduitError::DuplicateCategory)
+        ));
+        assert!(matches!(
+            info.rename(&business, CString::default()),
+            Err(ConduitError::EmptyCategoryName)
+        ));
+        assert_eq!(info.rename(&business, business.clone()).unwrap(), 1);
+        assert_eq!(
+            info.add(CString::new("Fifteen bytes!!").unwrap()).unwrap(),
+            2
+        );
+    }
+}
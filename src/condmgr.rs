@@ -168,6 +168,15 @@ impl ConduitManager {
         Ok(hs_path)
     }
 
+    /// Get the identifier HotSync writes to the handheld after syncing with this PC
+    pub(crate) fn get_pc_identifier(&self) -> Result<u32, ConduitError> {
+        let mut pc_id = std::mem::MaybeUninit::new(0);
+        unsafe {
+            return_iff_conduit_err!(self.api.CmGetPCIdentifier(pc_id.as_mut_ptr()));
+            Ok(pc_id.assume_init())
+        }
+    }
+
     pub fn install(
         self,
         builder: ConduitInstallation,
@@ -22,4 +22,5 @@ pub struct CondMgrApi {
     CmGetHotSyncExecPath:       unsafe extern "system" fn(pPath: *mut c_uchar, bufSize: *mut c_int) -> ConduitRegistrationError,
     CmGetConduitCount:          unsafe extern "system" fn() -> ConduitRegistrationError,
     CmGetLibVersion:            unsafe extern "system" fn() -> u16,
+    CmGetPCIdentifier:          unsafe extern "system" fn(pdwPCID: *mut u32) -> ConduitRegistrationError,
 }
@@ -0,0 +1,37 @@
+use std::{ffi::CString, time::SystemTime};
+
+use crate::syncmgr_extern::eDbFlags;
+
+/// Description of a database on the handheld
+#[derive(Debug, Clone)]
+pub struct DatabaseInfo {
+    pub name: CString,
+    pub card_no: u16,
+    /// Record/resource, read-only (ROM), backup, etc.
+    pub flags: eDbFlags,
+    /// Miscellaneous flags (exclude from sync, RAM based)
+    pub misc_flags: u32,
+    pub db_type: [u8; 4],
+    pub creator: [u8; 4],
+    pub version: u16,
+    pub mod_number: u32,
+    /// Position in the database list. Not returned by the find calls
+    pub index: u16,
+    pub create_date: Option<SystemTime>,
+    pub mod_date: Option<SystemTime>,
+    pub backup_date: Option<SystemTime>,
+}
+
+/// A database found by name or by type and creator
+#[derive(Debug, Clone)]
+pub struct FoundDatabase {
+    pub info: DatabaseInfo,
+    pub card_no: u32,
+    /// The handheld's memory ID for the database, valid until it is deleted
+    pub local_id: u32,
+    pub record_count: u32,
+    /// Total size of the database in bytes, including headers
+    pub total_bytes: u32,
+    /// Size of the largest record or resource. `None` from the find calls, which can't report it
+    pub max_record_size: Option<u32>,
+}
@@ -0,0 +1,92 @@
+use std::{ffi::CString, fmt, time::SystemTime};
+
+/// A Palm OS version, as reported for the ROM and the running OS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RomVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub fix: u8,
+    /// 0 for development, 1 for alpha, 2 for beta, 3 for release
+    pub stage: u8,
+    pub build: u16,
+}
+
+impl RomVersion {
+    /// Split the packed `0xMMmfsbbb` format used by the handheld
+    pub fn from_raw(raw: u32) -> Self {
+        Self {
+            major: (raw >> 24) as u8,
+            minor: ((raw >> 20) & 0xF) as u8,
+            fix: ((raw >> 16) & 0xF) as u8,
+            stage: ((raw >> 12) & 0xF) as u8,
+            build: (raw & 0xFFF) as u16,
+        }
+    }
+}
+
+impl fmt::Display for RomVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if self.fix != 0 {
+            write!(f, ".{}", self.fix)?;
+        }
+        Ok(())
+    }
+}
+
+/// The HotSync user the handheld belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserInfo {
+    pub name: CString,
+    pub user_id: u32,
+    pub viewer_id: u32,
+    /// Identifier of the PC the handheld last synced with
+    pub last_sync_pc: u32,
+    pub last_sync_date: Option<SystemTime>,
+}
+
+/// Description of the handheld itself
+#[derive(Debug, Clone, PartialEq)]
+pub struct SystemInfo {
+    pub rom_version: RomVersion,
+    pub local_id: u32,
+    /// Product ID text, which may not be printable
+    pub product_id: Vec<u8>,
+}
+
+/// Description of a memory card on the handheld, including its free space
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardInfo {
+    pub card_no: u8,
+    pub version: u16,
+    pub create_date: Option<SystemTime>,
+    pub rom_size: u32,
+    pub ram_size: u32,
+    pub free_ram: u32,
+    pub name: CString,
+    pub manufacturer: CString,
+    pub rom_db_count: u16,
+    pub ram_db_count: u16,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rom_version() {
+        let version = RomVersion::from_raw(0x0412_3005);
+        assert_eq!(
+            version,
+            RomVersion {
+                major: 4,
+                minor: 1,
+                fix: 2,
+                stage: 3,
+                build: 5,
+            }
+        );
+        assert_eq!(version.to_string(), "4.1.2");
+        assert!(RomVersion::from_raw(0x0350_3000) < version);
+    }
+}
@@ -1,5 +1,10 @@
 use std::{error::Error, fmt::Display};
 
+use crate::{
+    capabilities::{ApiVersion, Capability},
+    syncmgr_extern::MAX_RECORD_SIZE,
+};
+
 const SYNC_ERR_CLASS: i32 = 0x00004000;
 const SYNC_FATAL_ERR_MASK: i32 = 0x10000000;
 const SYNC_FATAL_ERR: i32 = SYNC_ERR_CLASS + SYNC_FATAL_ERR_MASK;
@@ -201,6 +206,31 @@ pub enum ConduitError {
     PreferenceSerde,
     /// The requested application preference does not exist on the device
     NoSuchPreference,
+    /// A resource in the source database is missing its type or ID
+    InvalidResource,
+    /// The database has no AppInfo block in the standard category layout
+    InvalidAppInfo,
+    /// No category with the given name exists in the database
+    NoSuchCategory,
+    /// All 15 user categories of the database are in use
+    CategoriesFull,
+    /// A category name can't be empty, since an empty name marks an unused slot
+    EmptyCategoryName,
+    /// A category name of this many bytes doesn't fit in the 15 bytes of its slot
+    CategoryNameTooLong(usize),
+    /// Another category already has the requested name
+    DuplicateCategory,
+    /// Unfiled, the first category, can't be removed
+    CannotRemoveUnfiled,
+    /// A category can't be merged into itself
+    MergeIntoSelf,
+    /// The Sync Manager API version, given here, is too old for the requested capability
+    Unsupported(Capability, ApiVersion),
+    /// A record, resource, or info block of this many bytes is over `MAX_RECORD_SIZE`
+    RecordTooLarge(usize),
+    /// A blob is too large to split into chunk records, or its chunks on the handheld are
+    /// missing, malformed, or don't match their checksum
+    InvalidBlob,
 }
 
 impl From<Box<dyn Error + Send + Sync>> for ConduitError {
@@ -247,6 +277,40 @@ impl Display for ConduitError {
                 f,
                 "The requested application preference does not exist on the device"
             )?,
+            ConduitError::InvalidResource => {
+                writeln!(f, "Resource in source database is missing its type or ID")?
+            }
+            ConduitError::InvalidAppInfo => {
+                writeln!(f, "Database has no standard category AppInfo block")?
+            }
+            ConduitError::NoSuchCategory => writeln!(f, "The requested category does not exist")?,
+            ConduitError::CategoriesFull => writeln!(f, "No free categories left in database")?,
+            ConduitError::EmptyCategoryName => writeln!(f, "Category names can't be empty")?,
+            ConduitError::CategoryNameTooLong(len) => {
+                writeln!(f, "{len} byte category name is over the 15 byte limit")?
+            }
+            ConduitError::DuplicateCategory => {
+                writeln!(f, "A category with that name already exists")?
+            }
+            ConduitError::CannotRemoveUnfiled => {
+                writeln!(f, "The Unfiled category can't be removed")?
+            }
+            ConduitError::MergeIntoSelf => writeln!(f, "A category can't be merged into itself")?,
+            ConduitError::RecordTooLarge(len) => writeln!(
+                f,
+                "{len} bytes is over the {MAX_RECORD_SIZE} byte limit for a single record"
+            )?,
+            ConduitError::InvalidBlob => writeln!(
+                f,
+                "Blob is too large to store, or its chunks are missing or corrupt"
+            )?,
+            ConduitError::Unsupported(capability, (major, minor)) => {
+                let (min_major, min_minor) = capability.min_api_version();
+                writeln!(
+                    f,
+                    "{capability:?} requires Sync Manager API {min_major}.{min_minor} or later, found {major}.{minor}"
+                )?
+            }
             _ => (),
         };
         match self {
@@ -1,13 +1,35 @@
 #![cfg(target_os = "windows")]
+mod backup;
 mod condmgr;
 mod condmgr_extern;
 
 mod syncmgr;
 mod syncmgr_extern;
 
+mod blob;
+mod capabilities;
+mod category;
+mod database_info;
+mod device_info;
 mod error;
+mod mirror;
+mod palm_time;
+mod pdb_file;
+mod record_sync;
 
+pub use backup::RestoreOptions;
+pub use capabilities::{ApiVersion, Capabilities, Capability};
+pub use category::{CategoryAppInfo, CategoryEdit, CATEGORY_COUNT};
 pub use condmgr::{ConduitInstallation, ConduitManager};
-pub use error::SyncManagerError;
+pub use database_info::{DatabaseInfo, FoundDatabase};
+pub use device_info::{CardInfo, RomVersion, SystemInfo, UserInfo};
+pub use error::{ConduitError, ConduitRegistrationError, SyncManagerError};
+pub use pdb_file::InfoBlocks;
+pub use record_sync::{
+    merge, overwrite, ConflictPolicy, DesktopStore, MergeResult, RecordChange, RecordDiff,
+    SyncRecord,
+};
 pub use syncmgr::*;
-pub use syncmgr_extern::CSyncProperties;
+pub use syncmgr_extern::{
+    eConnType, eDbFlags, eFirstSync, eSyncTypes, CSyncProperties, MAX_RECORD_SIZE,
+};
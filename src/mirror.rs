@@ -0,0 +1,229 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+use palmrs::database::record::pdb_record::RecordAttributes;
+
+use crate::record_sync::{same_contents, RecordChange, SyncRecord};
+
+const MIRROR_MAGIC: &[u8; 4] = b"HSMR";
+const MIRROR_VERSION: u16 = 1;
+const DELETED_BIT: u8 = 0x80;
+/// Only meaningful on a deleted record, where it takes the place of the category
+const ARCHIVED_BIT: u8 = 0x08;
+
+/// Snapshot of a handheld database as of the last sync with this PC, used to work out what
+/// actually changed when the handheld's dirty flags can't be trusted
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct DesktopMirror {
+    records: BTreeMap<u32, SyncRecord>,
+}
+
+impl DesktopMirror {
+    /// Load the mirror from disk, starting empty if it hasn't been written yet
+    pub(crate) fn load(path: &Path) -> Result<Self, Error> {
+        match std::fs::read(path) {
+            Ok(bytes) => Self::from_bytes(&bytes),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_bytes())
+    }
+
+    fn from_bytes(mut bytes: &[u8]) -> Result<Self, Error> {
+        if take(&mut bytes, 4)? != MIRROR_MAGIC || take_u16(&mut bytes)? != MIRROR_VERSION {
+            return Err(Error::new(ErrorKind::InvalidData, "Not a desktop mirror"));
+        }
+        let count = take_u32(&mut bytes)?;
+        let mut records = BTreeMap::new();
+        for _ in 0..count {
+            let id = take_u32(&mut bytes)?;
+            let attributes = RecordAttributes::from(take(&mut bytes, 1)?[0]);
+            let len = take_u32(&mut bytes)?;
+            let data = take(&mut bytes, len as usize)?.to_vec();
+            records.insert(
+                id,
+                SyncRecord {
+                    id,
+                    attributes,
+                    data,
+                },
+            );
+        }
+        Ok(Self { records })
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut ret = Vec::new();
+        ret.extend_from_slice(MIRROR_MAGIC);
+        ret.extend_from_slice(&MIRROR_VERSION.to_be_bytes());
+        ret.extend_from_slice(&(self.records.len() as u32).to_be_bytes());
+        for record in self.records.values() {
+            ret.extend_from_slice(&record.id.to_be_bytes());
+            ret.push(u8::from(record.attributes));
+            ret.extend_from_slice(&(record.data.len() as u32).to_be_bytes());
+            ret.extend_from_slice(&record.data);
+        }
+        ret
+    }
+
+    /// Compare every record on the handheld against the mirror to find the real adds, changes,
+    /// and deletes since the last sync with this PC. Archived records are always reported, as in a
+    /// fast sync, so the desktop keeps its copy
+    pub(crate) fn diff(&self, handheld: Vec<SyncRecord>) -> Vec<RecordChange> {
+        let mut seen = HashSet::new();
+        let mut ret = Vec::new();
+        for record in handheld {
+            seen.insert(record.id);
+            let known = self.records.get(&record.id);
+            let attributes = u8::from(record.attributes);
+            if attributes & DELETED_BIT != 0 {
+                if attributes & ARCHIVED_BIT != 0 {
+                    ret.push(RecordChange::Archived(record));
+                } else if known.is_some() {
+                    ret.push(RecordChange::Deleted(record.id));
+                }
+            } else if !known.is_some_and(|known| same_contents(known, &record)) {
+                ret.push(RecordChange::Modified(record));
+            }
+        }
+        ret.extend(
+            self.records
+                .keys()
+                .filter(|id| !seen.contains(id))
+                .map(|id| RecordChange::Deleted(*id)),
+        );
+        ret
+    }
+
+    /// The last synced state of a record, if the handheld had it
+    pub(crate) fn get(&self, id: u32) -> Option<SyncRecord> {
+        self.records.get(&id).cloned()
+    }
+
+    /// Record changes that have been made on the handheld
+    pub(crate) fn apply(&mut self, changes: &[RecordChange]) {
+        for change in changes {
+            match change {
+                RecordChange::Modified(record) => {
+                    self.records.insert(record.id, record.clone());
+                }
+                RecordChange::Deleted(id) | RecordChange::Archived(SyncRecord { id, .. }) => {
+                    self.records.remove(id);
+                }
+            }
+        }
+    }
+
+    /// Record a desktop record that the handheld stored under `new_id`
+    pub(crate) fn record_written(&mut self, record: &SyncRecord, new_id: u32) {
+        self.records.remove(&record.id);
+        self.records.insert(
+            new_id,
+            SyncRecord {
+                id: new_id,
+                ..record.clone()
+            },
+        );
+    }
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if bytes.len() < len {
+        return Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "Truncated desktop mirror",
+        ));
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+fn take_u16(bytes: &mut &[u8]) -> Result<u16, Error> {
+    Ok(u16::from_be_bytes(take(bytes, 2)?.try_into().unwrap()))
+}
+
+fn take_u32(bytes: &mut &[u8]) -> Result<u32, Error> {
+    Ok(u32::from_be_bytes(take(bytes, 4)?.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rec(id: u32, data: &[u8]) -> SyncRecord {
+        SyncRecord {
+            id,
+            attributes: RecordAttributes::from(0),
+            data: data.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_mirror_round_trip_and_diff() {
+        let mut mirror = DesktopMirror::default();
+        mirror.apply(&[
+            RecordChange::Modified(rec(1, b"unchanged")),
+            RecordChange::Modified(rec(2, b"before")),
+            RecordChange::Modified(rec(3, b"deleted")),
+        ]);
+        let mirror = DesktopMirror::from_bytes(&mirror.to_bytes()).unwrap();
+
+        let changes = mirror.diff(vec![
+            rec(1, b"unchanged"),
+            rec(2, b"after"),
+            rec(4, b"added"),
+        ]);
+        assert_eq!(
+            changes,
+            vec![
+                RecordChange::Modified(rec(2, b"after")),
+                RecordChange::Modified(rec(4, b"added")),
+                RecordChange::Deleted(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_keeps_archived_records() {
+        let mut mirror = DesktopMirror::default();
+        mirror.apply(&[
+            RecordChange::Modified(rec(1, b"archived")),
+            RecordChange::Modified(rec(2, b"deleted")),
+        ]);
+        let deleted = |id, attribs: u8, data: &[u8]| SyncRecord {
+            attributes: RecordAttributes::from(attribs),
+            ..rec(id, data)
+        };
+        let archived = deleted(1, DELETED_BIT | ARCHIVED_BIT, b"archived");
+        let new_archived = deleted(3, DELETED_BIT | ARCHIVED_BIT, b"new");
+
+        let changes = mirror.diff(vec![
+            archived.clone(),
+            deleted(2, DELETED_BIT, b""),
+            new_archived.clone(),
+        ]);
+        assert_eq!(
+            changes,
+            vec![
+                RecordChange::Archived(archived.clone()),
+                RecordChange::Deleted(2),
+                RecordChange::Archived(new_archived.clone()),
+            ]
+        );
+        // the same records read during a fast sync
+        assert_eq!(
+            RecordChange::from_raw(1, DELETED_BIT | ARCHIVED_BIT, 0, b"archived".to_vec()),
+            RecordChange::Archived(archived)
+        );
+    }
+}
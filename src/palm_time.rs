@@ -0,0 +1,78 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Seconds between the Palm OS epoch (1904-01-01) and the Unix epoch
+const PALM_EPOCH_OFFSET: u64 = 2_082_844_800;
+
+/// Convert Palm OS seconds-since-1904 to a `SystemTime`. Palm OS uses 0 for dates that were never set
+pub(crate) fn from_palm_seconds(secs: u32) -> Option<SystemTime> {
+    let secs = secs as u64;
+    match secs {
+        0 => None,
+        secs if secs >= PALM_EPOCH_OFFSET => {
+            Some(UNIX_EPOCH + Duration::from_secs(secs - PALM_EPOCH_OFFSET))
+        }
+        secs => Some(UNIX_EPOCH - Duration::from_secs(PALM_EPOCH_OFFSET - secs)),
+    }
+}
+
+/// Convert a `SystemTime` to Palm OS seconds-since-1904, using 0 for unset dates
+pub(crate) fn to_palm_seconds(time: Option<SystemTime>) -> u32 {
+    let Some(time) = time else {
+        return 0;
+    };
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(since) => PALM_EPOCH_OFFSET + since.as_secs(),
+        Err(e) => PALM_EPOCH_OFFSET.saturating_sub(e.duration().as_secs()),
+    };
+    secs.min(u32::MAX as u64) as u32
+}
+
+/// Convert Palm OS seconds-since-1904 in local time to a `SystemTime`, given the local time's
+/// offset from UTC in seconds (positive east of Greenwich)
+pub(crate) fn from_palm_local(secs: u32, utc_offset: i32) -> Option<SystemTime> {
+    let local = from_palm_seconds(secs)?;
+    let offset = Duration::from_secs(utc_offset.unsigned_abs() as u64);
+    if utc_offset >= 0 {
+        local.checked_sub(offset)
+    } else {
+        local.checked_add(offset)
+    }
+}
+
+/// Convert a `SystemTime` to Palm OS seconds-since-1904 in local time, given the local time's
+/// offset from UTC in seconds (positive east of Greenwich)
+pub(crate) fn to_palm_local(time: SystemTime, utc_offset: i32) -> u32 {
+    let offset = Duration::from_secs(utc_offset.unsigned_abs() as u64);
+    let local = if utc_offset >= 0 {
+        time.checked_add(offset)
+    } else {
+        time.checked_sub(offset)
+    };
+    to_palm_seconds(local)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_palm_epoch() {
+        assert_eq!(from_palm_seconds(0), None);
+        assert_eq!(
+            from_palm_seconds(PALM_EPOCH_OFFSET as u32),
+            Some(UNIX_EPOCH)
+        );
+        assert_eq!(to_palm_seconds(None), 0);
+        assert_eq!(to_palm_seconds(from_palm_seconds(12345)), 12345);
+
+        let utc_plus_2 = 2 * 60 * 60;
+        assert_eq!(
+            from_palm_local(PALM_EPOCH_OFFSET as u32 + utc_plus_2, utc_plus_2 as i32),
+            Some(UNIX_EPOCH)
+        );
+        assert_eq!(
+            to_palm_local(UNIX_EPOCH, -3600),
+            PALM_EPOCH_OFFSET as u32 - 3600
+        );
+    }
+}
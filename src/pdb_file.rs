@@ -0,0 +1,410 @@
+use std::{
+    ffi::CString,
+    io::{Error, ErrorKind},
+};
+
+use palmrs::database::record::pdb_record::RecordAttributes;
+
+use crate::{
+    database_info::DatabaseInfo,
+    palm_time::{from_palm_seconds, to_palm_seconds},
+    syncmgr_extern::eDbFlags,
+};
+
+/// Offsets into the header shared by .pdb and .prc files
+const NAME_LEN: usize = 32;
+const APP_INFO_OFFSET: usize = 52;
+const SORT_INFO_OFFSET: usize = 56;
+const ATTRIBUTES_OFFSET: usize = 32;
+const NUM_RECORDS_OFFSET: usize = 76;
+const HEADER_LEN: usize = 78;
+const RECORD_ENTRY_LEN: usize = 8;
+const RESOURCE_ENTRY_LEN: usize = 10;
+const RESOURCE_DB: u16 = 0x0001;
+
+/// The AppInfo and SortInfo blocks of a database. Most applications keep their category names in
+/// the AppInfo block
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InfoBlocks {
+    pub app_info: Option<Vec<u8>>,
+    pub sort_info: Option<Vec<u8>>,
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+/// Offset of the first record or resource, which ends whichever info block comes last
+fn first_entry_offset(bytes: &[u8]) -> Option<usize> {
+    let count = read_u16(bytes, NUM_RECORDS_OFFSET)?;
+    if count == 0 {
+        return None;
+    }
+    let (entry_len, offset_in_entry) = if read_u16(bytes, ATTRIBUTES_OFFSET)? & RESOURCE_DB != 0 {
+        // type and ID come before the offset
+        (RESOURCE_ENTRY_LEN, 6)
+    } else {
+        (RECORD_ENTRY_LEN, 0)
+    };
+    (0..count as usize)
+        .filter_map(|idx| read_u32(bytes, HEADER_LEN + idx * entry_len + offset_in_entry))
+        .map(|offset| offset as usize)
+        .min()
+}
+
+/// Pull the AppInfo and SortInfo blocks out of the raw bytes of a .pdb or .prc file
+pub(crate) fn info_blocks(bytes: &[u8]) -> InfoBlocks {
+    let app_start = read_u32(bytes, APP_INFO_OFFSET).unwrap_or(0) as usize;
+    let sort_start = read_u32(bytes, SORT_INFO_OFFSET).unwrap_or(0) as usize;
+    let end = first_entry_offset(bytes).unwrap_or(bytes.len());
+
+    let block = |start: usize, end: usize| {
+        (start != 0)
+            .then(|| bytes.get(start..end))
+            .flatten()
+            .map(<[u8]>::to_vec)
+    };
+    InfoBlocks {
+        app_info: block(
+            app_start,
+            if sort_start > app_start {
+                sort_start
+            } else {
+                end
+            },
+        ),
+        sort_info: block(sort_start, end),
+    }
+}
+
+/// The records or resources of a database, in order
+pub(crate) enum DbEntries {
+    /// Data, attributes, and unique ID of each record
+    Records(Vec<(Vec<u8>, RecordAttributes, u32)>),
+    /// Data, type, and ID of each resource
+    Resources(Vec<(Vec<u8>, [u8; 4], u16)>),
+}
+
+impl DbEntries {
+    fn len(&self) -> usize {
+        match self {
+            DbEntries::Records(records) => records.len(),
+            DbEntries::Resources(resources) => resources.len(),
+        }
+    }
+}
+
+/// A file name for the database that is safe to use on Windows, with the usual extension
+pub(crate) fn file_name(name: &CString, resource: bool) -> String {
+    let name: String = String::from_utf8_lossy(name.as_bytes())
+        .chars()
+        .map(|c| match c {
+            '\\' | '/' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    format!("{name}.{}", if resource { "prc" } else { "pdb" })
+}
+
+/// Serialize a database downloaded from the handheld as a complete .pdb or .prc file
+pub(crate) fn to_bytes(info: &DatabaseInfo, blocks: &InfoBlocks, entries: &DbEntries) -> Vec<u8> {
+    let (entry_len, resource) = match entries {
+        DbEntries::Records(_) => (RECORD_ENTRY_LEN, 0),
+        DbEntries::Resources(_) => (RESOURCE_ENTRY_LEN, RESOURCE_DB),
+    };
+    // the entry list is followed by two bytes of padding
+    let mut offset = HEADER_LEN + entries.len() * entry_len + 2;
+    let mut place = |block: &Option<Vec<u8>>| {
+        block.as_ref().map_or(0, |block| {
+            let start = offset;
+            offset += block.len();
+            start as u32
+        })
+    };
+    let app_info_offset = place(&blocks.app_info);
+    let sort_info_offset = place(&blocks.sort_info);
+
+    let mut bytes = Vec::new();
+    let mut name = [0_u8; NAME_LEN];
+    let name_len = info.name.as_bytes().len().min(NAME_LEN - 1);
+    name[..name_len].copy_from_slice(&info.name.as_bytes()[..name_len]);
+    bytes.extend_from_slice(&name);
+    let attributes = (info.flags.bits() as u16 & !(eDbFlags::eOpenDb.bits() as u16)) | resource;
+    bytes.extend_from_slice(&attributes.to_be_bytes());
+    bytes.extend_from_slice(&info.version.to_be_bytes());
+    for date in [info.create_date, info.mod_date, info.backup_date] {
+        bytes.extend_from_slice(&to_palm_seconds(date).to_be_bytes());
+    }
+    bytes.extend_from_slice(&info.mod_number.to_be_bytes());
+    bytes.extend_from_slice(&app_info_offset.to_be_bytes());
+    bytes.extend_from_slice(&sort_info_offset.to_be_bytes());
+    bytes.extend_from_slice(&info.db_type);
+    bytes.extend_from_slice(&info.creator);
+    let unique_id_seed = match entries {
+        DbEntries::Records(records) => records.iter().map(|(_, _, id)| id + 1).max(),
+        DbEntries::Resources(_) => None,
+    };
+    bytes.extend_from_slice(&unique_id_seed.unwrap_or(0).to_be_bytes());
+    // next record list
+    bytes.extend_from_slice(&0_u32.to_be_bytes());
+    bytes.extend_from_slice(&(entries.len() as u16).to_be_bytes());
+
+    let mut data: Vec<&[u8]> = Vec::with_capacity(entries.len());
+    match entries {
+        DbEntries::Records(records) => {
+            for (record, attributes, id) in records {
+                bytes.extend_from_slice(&(offset as u32).to_be_bytes());
+                bytes.push(u8::from(*attributes));
+                bytes.extend_from_slice(&id.to_be_bytes()[1..]);
+                offset += record.len();
+                data.push(record);
+            }
+        }
+        DbEntries::Resources(resources) => {
+            for (resource, ty, id) in resources {
+                bytes.extend_from_slice(ty);
+                bytes.extend_from_slice(&id.to_be_bytes());
+                bytes.extend_from_slice(&(offset as u32).to_be_bytes());
+                offset += resource.len();
+                data.push(resource);
+            }
+        }
+    }
+    bytes.extend_from_slice(&[0, 0]);
+
+    for block in [&blocks.app_info, &blocks.sort_info].into_iter().flatten() {
+        bytes.extend_from_slice(block);
+    }
+    for entry in data {
+        bytes.extend_from_slice(entry);
+    }
+    bytes
+}
+
+/// Parse a complete .pdb or .prc file, keeping the header info, info blocks, and the attributes
+/// and unique IDs of every record
+pub(crate) fn from_bytes(bytes: &[u8]) -> Result<(DatabaseInfo, InfoBlocks, DbEntries), Error> {
+    let invalid = || Error::new(ErrorKind::InvalidData, "Invalid Palm database");
+    let header = bytes.get(..HEADER_LEN).ok_or_else(invalid)?;
+    let name_len = header[..NAME_LEN]
+        .iter()
+        .position(|c| *c == 0)
+        .unwrap_or(NAME_LEN);
+    let attributes = read_u16(header, ATTRIBUTES_OFFSET).ok_or_else(invalid)?;
+    let date = |offset| from_palm_seconds(read_u32(header, offset).unwrap_or(0));
+    let info = DatabaseInfo {
+        name: CString::new(&header[..name_len]).unwrap(),
+        card_no: 0,
+        flags: eDbFlags::from_bits_retain(attributes as u32),
+        misc_flags: 0,
+        db_type: header[60..64].try_into().unwrap(),
+        creator: header[64..68].try_into().unwrap(),
+        version: read_u16(header, 34).ok_or_else(invalid)?,
+        mod_number: read_u32(header, 48).ok_or_else(invalid)?,
+        index: 0,
+        create_date: date(36),
+        mod_date: date(40),
+        backup_date: date(44),
+    };
+
+    let count = read_u16(header, NUM_RECORDS_OFFSET).ok_or_else(invalid)? as usize;
+    let resource = attributes & RESOURCE_DB != 0;
+    let (entry_len, offset_in_entry) = if resource {
+        (RESOURCE_ENTRY_LEN, 6)
+    } else {
+        (RECORD_ENTRY_LEN, 0)
+    };
+    let entry = |idx: usize| {
+        bytes
+            .get(HEADER_LEN + idx * entry_len..HEADER_LEN + (idx + 1) * entry_len)
+            .ok_or_else(invalid)
+    };
+    let offsets = (0..count)
+        .map(|idx| {
+            read_u32(entry(idx)?, offset_in_entry)
+                .map(|offset| offset as usize)
+                .ok_or_else(invalid)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // each entry runs until the next one starts
+    let data = |idx: usize| {
+        let end = offsets.get(idx + 1).copied().unwrap_or(bytes.len());
+        bytes
+            .get(offsets[idx]..end)
+            .map(<[u8]>::to_vec)
+            .ok_or_else(invalid)
+    };
+
+    let entries = if resource {
+        DbEntries::Resources(
+            (0..count)
+                .map(|idx| {
+                    let entry = entry(idx)?;
+                    let id = read_u16(entry, 4).ok_or_else(invalid)?;
+                    Ok((data(idx)?, entry[..4].try_into().unwrap(), id))
+                })
+                .collect::<Result<_, Error>>()?,
+        )
+    } else {
+        DbEntries::Records(
+            (0..count)
+                .map(|idx| {
+                    let entry = entry(idx)?;
+                    let id = u32::from_be_bytes([0, entry[5], entry[6], entry[7]]);
+                    Ok((data(idx)?, RecordAttributes::from(entry[4]), id))
+                })
+                .collect::<Result<_, Error>>()?,
+        )
+    };
+    Ok((info, info_blocks(bytes), entries))
+}
+
+#[cfg(test)]
+mod test {
+    use palmrs::database::{record::DatabaseRecord, PalmDatabase, PdbDatabase};
+
+    use super::*;
+
+    #[test]
+    fn test_info_blocks() {
+        let mut pdb = vec![0_u8; HEADER_LEN];
+        pdb[APP_INFO_OFFSET..APP_INFO_OFFSET + 4].copy_from_slice(&88_u32.to_be_bytes());
+        pdb[SORT_INFO_OFFSET..SORT_INFO_OFFSET + 4].copy_from_slice(&92_u32.to_be_bytes());
+        pdb[NUM_RECORDS_OFFSET..NUM_RECORDS_OFFSET + 2].copy_from_slice(&1_u16.to_be_bytes());
+        // one record entry, then two bytes of padding
+        pdb.extend_from_slice(&96_u32.to_be_bytes());
+        pdb.extend_from_slice(&[0; 6]);
+        pdb.extend_from_slice(b"appisort");
+        pdb.extend_from_slice(b"record");
+
+        assert_eq!(
+            info_blocks(&pdb),
+            InfoBlocks {
+                app_info: Some(b"appi".to_vec()),
+                sort_info: Some(b"sort".to_vec()),
+            }
+        );
+
+        pdb[SORT_INFO_OFFSET..SORT_INFO_OFFSET + 4].copy_from_slice(&0_u32.to_be_bytes());
+        assert_eq!(
+            info_blocks(&pdb),
+            InfoBlocks {
+                app_info: Some(b"appisort".to_vec()),
+                sort_info: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_write_database() {
+        let info = DatabaseInfo {
+            name: CString::new("HeffalumpContentDB").unwrap(),
+            card_no: 0,
+            flags: eDbFlags::eBackupDb | eDbFlags::eOpenDb,
+            misc_flags: 0,
+            db_type: *b"DATA",
+            creator: *b"Test",
+            version: 1,
+            mod_number: 7,
+            index: 0,
+            create_date: None,
+            mod_date: None,
+            backup_date: None,
+        };
+        let blocks = InfoBlocks {
+            app_info: Some(b"appinfo".to_vec()),
+            sort_info: None,
+        };
+        let records = DbEntries::Records(vec![
+            (b"one".to_vec(), RecordAttributes::from(0), 0x10_0001),
+            (b"two".to_vec(), RecordAttributes::from(0), 0x10_0002),
+        ]);
+
+        let bytes = to_bytes(&info, &blocks, &records);
+        assert_eq!(&bytes[..18], b"HeffalumpContentDB");
+        assert_eq!(read_u16(&bytes, ATTRIBUTES_OFFSET), Some(0x0008));
+        assert_eq!(&bytes[60..68], b"DATATest");
+        assert_eq!(read_u16(&bytes, NUM_RECORDS_OFFSET), Some(2));
+        assert_eq!(info_blocks(&bytes), blocks);
+        assert_eq!(
+            read_u32(&bytes, HEADER_LEN + RECORD_ENTRY_LEN + 4),
+            Some(0x0010_0002)
+        );
+        assert!(bytes.ends_with(b"appinfoonetwo"));
+
+        let (parsed, parsed_blocks, parsed_records) = from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.name, info.name);
+        assert_eq!(parsed.mod_number, 7);
+        assert_eq!(parsed_blocks, blocks);
+        let DbEntries::Records(parsed_records) = parsed_records else {
+            panic!("Expected records");
+        };
+        assert_eq!(parsed_records[1].0, b"two");
+        assert_eq!(parsed_records[1].2, 0x10_0002);
+        assert_eq!(
+            file_name(&CString::new("a/b").unwrap(), true),
+            "a_b.prc".to_string()
+        );
+    }
+
+    #[test]
+    fn test_palmrs_reads_written_files() {
+        let mut info = DatabaseInfo {
+            name: CString::new("HeffalumpContentDB").unwrap(),
+            card_no: 0,
+            flags: eDbFlags::eBackupDb,
+            misc_flags: 0,
+            db_type: *b"DATA",
+            creator: *b"Test",
+            version: 1,
+            mod_number: 7,
+            index: 0,
+            create_date: None,
+            mod_date: None,
+            backup_date: None,
+        };
+        let blocks = InfoBlocks {
+            app_info: Some(b"appinfo".to_vec()),
+            sort_info: None,
+        };
+
+        let records = DbEntries::Records(vec![
+            (b"one".to_vec(), RecordAttributes::from(0x40), 0x10_0001),
+            (b"two".to_vec(), RecordAttributes::from(0x02), 0x10_0002),
+        ]);
+        let pdb =
+            PalmDatabase::<PdbDatabase>::from_bytes(&to_bytes(&info, &blocks, &records)).unwrap();
+        assert_eq!(pdb.header.attributes & RESOURCE_DB, 0);
+        let parsed = pdb.list_records_resources();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].1, b"one");
+        assert_eq!(parsed[1].1, b"two");
+        assert_eq!(parsed[1].0.unique_id(), Some(0x10_0002));
+        assert_eq!(parsed[1].0.attributes(), Some(RecordAttributes::from(0x02)));
+
+        info.flags |= eDbFlags::eResource;
+        info.db_type = *b"appl";
+        let resources = DbEntries::Resources(vec![
+            (b"code".to_vec(), *b"code", 1),
+            (b"name".to_vec(), *b"tAIN", 1000),
+        ]);
+        let prc =
+            PalmDatabase::<PdbDatabase>::from_bytes(&to_bytes(&info, &blocks, &resources)).unwrap();
+        assert_eq!(prc.header.attributes & RESOURCE_DB, RESOURCE_DB);
+        let parsed = prc.list_records_resources();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].1, b"code");
+        assert_eq!(parsed[1].0.name_str(), Some("tAIN"));
+        assert_eq!(parsed[1].0.resource_id(), Some(1000));
+        assert_eq!(parsed[1].1, b"name");
+    }
+}
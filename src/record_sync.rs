@@ -0,0 +1,332 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+};
+
+use palmrs::database::record::pdb_record::RecordAttributes;
+
+use crate::syncmgr_extern::eSyncRecAttrs;
+
+/// A single record, as seen by either the handheld or the desktop
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncRecord {
+    /// Unique ID assigned by the handheld. Records that have never been written to the handheld use 0
+    pub id: u32,
+    pub attributes: RecordAttributes,
+    pub data: Vec<u8>,
+}
+
+/// A change to a single record since the last sync
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordChange {
+    /// The record was added or modified
+    Modified(SyncRecord),
+    /// The record with this unique ID was deleted
+    Deleted(u32),
+    /// The record was deleted on the handheld, but the user asked to keep a copy on the desktop
+    Archived(SyncRecord),
+}
+
+impl RecordChange {
+    pub fn id(&self) -> u32 {
+        match self {
+            RecordChange::Modified(rec) | RecordChange::Archived(rec) => rec.id,
+            RecordChange::Deleted(id) => *id,
+        }
+    }
+
+    /// Build a change from the raw fields returned by the Sync Manager record calls
+    pub(crate) fn from_raw(id: u32, attribs: u8, category: i16, data: Vec<u8>) -> Self {
+        let flags = eSyncRecAttrs::from_bits_truncate(attribs as u32);
+        let record = SyncRecord {
+            id,
+            attributes: attributes_from_raw(attribs, category),
+            data,
+        };
+        if flags.contains(eSyncRecAttrs::eRecAttrArchived) {
+            RecordChange::Archived(record)
+        } else if flags.contains(eSyncRecAttrs::eRecAttrDeleted) {
+            RecordChange::Deleted(id)
+        } else {
+            RecordChange::Modified(record)
+        }
+    }
+}
+
+/// The Sync Manager reports the category separately from the attribute bits, and reuses the low
+/// nibble of the attributes for the archive flag. A PDB keeps the archive flag in place of the
+/// category once a record is deleted
+pub(crate) fn attributes_from_raw(attribs: u8, category: i16) -> RecordAttributes {
+    if eSyncRecAttrs::from_bits_truncate(attribs as u32).contains(eSyncRecAttrs::eRecAttrDeleted) {
+        RecordAttributes::from(attribs & (0xF0 | eSyncRecAttrs::eRecAttrArchived.bits() as u8))
+    } else {
+        RecordAttributes::from((attribs & 0xF0) | (category as u8 & 0x0F))
+    }
+}
+
+/// Split record attributes into the attribute bits and category the Sync Manager expects when
+/// writing. In a PDB the low nibble of a deleted record's attributes is the archive flag rather
+/// than the category, and the busy bit belongs to the handheld so it is never written
+pub(crate) fn attributes_to_raw(attributes: RecordAttributes) -> (u8, i16) {
+    let raw = u8::from(attributes);
+    let flags = raw & 0xF0 & !(eSyncRecAttrs::eRecAttrBusy.bits() as u8);
+    if eSyncRecAttrs::from_bits_truncate(raw as u32).contains(eSyncRecAttrs::eRecAttrDeleted) {
+        (
+            flags | (raw & eSyncRecAttrs::eRecAttrArchived.bits() as u8),
+            0,
+        )
+    } else {
+        (flags, (raw & 0x0F) as i16)
+    }
+}
+
+/// Whether two versions of a record hold the same data, ignoring the sync bookkeeping bits
+pub(crate) fn same_contents(a: &SyncRecord, b: &SyncRecord) -> bool {
+    // secret bit and category
+    const CONTENT_BITS: u8 = 0x1F;
+    a.data == b.data
+        && u8::from(a.attributes) & CONTENT_BITS == u8::from(b.attributes) & CONTENT_BITS
+}
+
+/// Changes to apply to a database already on the handheld, keyed by unique ID
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecordDiff {
+    /// New records. An ID of 0 lets the handheld assign one
+    pub added: Vec<SyncRecord>,
+    pub modified: Vec<SyncRecord>,
+    pub deleted: Vec<u32>,
+}
+
+impl RecordDiff {
+    pub(crate) fn into_changes(self) -> Vec<RecordChange> {
+        self.added
+            .into_iter()
+            .chain(self.modified)
+            .map(RecordChange::Modified)
+            .chain(self.deleted.into_iter().map(RecordChange::Deleted))
+            .collect()
+    }
+}
+
+/// How to resolve a record that was modified on both the handheld and the desktop
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConflictPolicy {
+    /// Keep the handheld version on both sides
+    HandheldWins,
+    /// Keep the desktop version on both sides
+    DesktopWins,
+    /// Keep the handheld version, and add the desktop version to both sides as a new record
+    #[default]
+    KeepBoth,
+}
+
+/// The desktop side of a two-way record sync
+pub trait DesktopStore {
+    /// Records added, modified, or deleted on the desktop since the last sync
+    fn desktop_changes(&mut self) -> Result<Vec<RecordChange>, Box<dyn Error + Sync + Send>>;
+
+    /// Apply the handheld changes that survived the merge to the desktop copy
+    fn apply_handheld_changes(
+        &mut self,
+        changes: Vec<RecordChange>,
+    ) -> Result<(), Box<dyn Error + Sync + Send>>;
+
+    /// Called with each desktop record written to the handheld, along with the unique ID the
+    /// handheld now stores it under
+    fn ids_assigned(
+        &mut self,
+        _assigned: Vec<(SyncRecord, u32)>,
+    ) -> Result<(), Box<dyn Error + Sync + Send>> {
+        Ok(())
+    }
+}
+
+/// The result of merging the handheld and desktop changes
+#[derive(Debug, Default, PartialEq)]
+pub struct MergeResult {
+    pub to_handheld: Vec<RecordChange>,
+    pub to_desktop: Vec<RecordChange>,
+}
+
+/// Merge the changes from both sides of a sync, keyed by unique ID
+pub fn merge(
+    handheld: Vec<RecordChange>,
+    desktop: Vec<RecordChange>,
+    policy: ConflictPolicy,
+) -> MergeResult {
+    let mut desktop: Vec<Option<RecordChange>> = desktop.into_iter().map(Some).collect();
+    let desktop_by_id: HashMap<u32, usize> = desktop
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, change)| change.as_ref().map(|c| (c.id(), idx)))
+        .filter(|(id, _)| *id != 0)
+        .collect();
+
+    let mut ret = MergeResult::default();
+    for hh in handheld {
+        let Some(pc) = desktop_by_id
+            .get(&hh.id())
+            .and_then(|idx| desktop[*idx].take())
+        else {
+            ret.to_desktop.push(hh);
+            continue;
+        };
+
+        match (hh, pc) {
+            // deleted on both sides, nothing left to do
+            (
+                RecordChange::Deleted(_) | RecordChange::Archived(_),
+                RecordChange::Deleted(_) | RecordChange::Archived(_),
+            ) => (),
+            // a modification always wins over a deletion
+            (
+                RecordChange::Deleted(_) | RecordChange::Archived(_),
+                pc @ RecordChange::Modified(_),
+            ) => ret.to_handheld.push(pc),
+            (
+                hh @ RecordChange::Modified(_),
+                RecordChange::Deleted(_) | RecordChange::Archived(_),
+            ) => ret.to_desktop.push(hh),
+            (RecordChange::Modified(hh), RecordChange::Modified(pc)) => {
+                if same_contents(&hh, &pc) {
+                    continue;
+                }
+                match policy {
+                    ConflictPolicy::HandheldWins => ret.to_desktop.push(RecordChange::Modified(hh)),
+                    ConflictPolicy::DesktopWins => ret.to_handheld.push(RecordChange::Modified(pc)),
+                    ConflictPolicy::KeepBoth => {
+                        ret.to_desktop.push(RecordChange::Modified(hh));
+                        ret.to_handheld
+                            .push(RecordChange::Modified(SyncRecord { id: 0, ..pc }));
+                    }
+                }
+            }
+        }
+    }
+
+    // anything left only changed on the desktop
+    ret.to_handheld.extend(desktop.into_iter().flatten());
+    ret
+}
+
+/// Send every change from the winning side to the other, and undo the losing side's own changes
+/// using `last_synced` to look up the state the records had after the last sync. Records
+/// `last_synced` doesn't know are left alone, since the mirror only holds what it has seen and
+/// they may be real data. Returns the changes to apply to the losing side
+pub fn overwrite(
+    winner: Vec<RecordChange>,
+    loser: Vec<RecordChange>,
+    last_synced: impl Fn(u32) -> Option<SyncRecord>,
+) -> Vec<RecordChange> {
+    let winner_ids: HashSet<u32> = winner.iter().map(RecordChange::id).collect();
+    let reverts = loser
+        .into_iter()
+        .map(|change| change.id())
+        // records the other side never saw can't be addressed
+        .filter(|id| *id != 0 && !winner_ids.contains(id))
+        .filter_map(|id| last_synced(id).map(RecordChange::Modified))
+        .collect::<Vec<_>>();
+    winner.into_iter().chain(reverts).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rec(id: u32, data: &[u8]) -> SyncRecord {
+        SyncRecord {
+            id,
+            attributes: RecordAttributes::from(0),
+            data: data.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_raw_attributes() {
+        const DELETED: u8 = 0x80;
+        const DIRTY: u8 = 0x40;
+        const BUSY: u8 = 0x20;
+        const SECRET: u8 = 0x10;
+        const ARCHIVED: u8 = 0x08;
+
+        for category in 0..16_u8 {
+            for flags in [0, DIRTY, SECRET, DIRTY | SECRET, BUSY | DIRTY | SECRET] {
+                // the Sync Manager may also report the archive flag on a live record
+                for raw in [flags, flags | ARCHIVED] {
+                    let attributes = attributes_from_raw(raw, category as i16);
+                    assert_eq!(u8::from(attributes), flags | category);
+                    assert_eq!(
+                        attributes_to_raw(attributes),
+                        (flags & !BUSY, category as i16)
+                    );
+                }
+            }
+        }
+
+        // deleted records keep the archive flag in place of the category
+        let archived = attributes_from_raw(DELETED | DIRTY | ARCHIVED, 5);
+        assert_eq!(u8::from(archived), DELETED | DIRTY | ARCHIVED);
+        assert_eq!(attributes_to_raw(archived), (DELETED | DIRTY | ARCHIVED, 0));
+        let deleted = attributes_from_raw(DELETED | BUSY | SECRET, 7);
+        assert_eq!(u8::from(deleted), DELETED | BUSY | SECRET);
+        assert_eq!(attributes_to_raw(deleted), (DELETED | SECRET, 0));
+    }
+
+    #[test]
+    fn test_merge() {
+        let handheld = vec![
+            RecordChange::Modified(rec(1, b"hh only")),
+            RecordChange::Modified(rec(2, b"hh conflict")),
+            RecordChange::Deleted(3),
+            RecordChange::Modified(rec(4, b"same")),
+        ];
+        let desktop = vec![
+            RecordChange::Modified(rec(2, b"pc conflict")),
+            RecordChange::Modified(rec(3, b"pc edit")),
+            RecordChange::Modified(rec(4, b"same")),
+            RecordChange::Modified(rec(0, b"pc new")),
+        ];
+
+        let merged = merge(handheld, desktop, ConflictPolicy::KeepBoth);
+        assert_eq!(
+            merged.to_desktop,
+            vec![
+                RecordChange::Modified(rec(1, b"hh only")),
+                RecordChange::Modified(rec(2, b"hh conflict")),
+            ]
+        );
+        assert_eq!(
+            merged.to_handheld,
+            vec![
+                RecordChange::Modified(rec(0, b"pc conflict")),
+                RecordChange::Modified(rec(3, b"pc edit")),
+                RecordChange::Modified(rec(0, b"pc new")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overwrite() {
+        let winner = vec![RecordChange::Modified(rec(1, b"winner"))];
+        let loser = vec![
+            RecordChange::Modified(rec(1, b"loser")),
+            RecordChange::Modified(rec(2, b"loser edit")),
+            RecordChange::Modified(rec(3, b"loser edit, not in mirror")),
+            RecordChange::Deleted(4),
+        ];
+
+        let changes = overwrite(winner.clone(), loser.clone(), |id| {
+            (id == 2).then(|| rec(2, b"synced"))
+        });
+        assert_eq!(
+            changes,
+            vec![
+                RecordChange::Modified(rec(1, b"winner")),
+                RecordChange::Modified(rec(2, b"synced")),
+            ]
+        );
+
+        // an empty mirror, as when there is no user folder, must never delete the loser's edits
+        assert_eq!(overwrite(winner.clone(), loser, |_| None), winner);
+    }
+}
@@ -1,21 +1,42 @@
 use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
     error::Error,
-    ffi::{c_uchar, CString},
+    ffi::{c_int, c_uchar, CString},
     mem::MaybeUninit,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
 };
 
 use dlopen2::wrapper::Container;
-use log::info;
+use log::{debug, info};
 use palmrs::database::{
     record::{pdb_record::RecordAttributes, DatabaseRecord},
     PalmDatabase, PdbDatabase,
 };
 
 use crate::{
+    backup::{BackupManifest, RestoreOptions},
+    blob::{join_blobs, split_blob},
+    capabilities::{Capabilities, Capability},
+    category::{CategoryAppInfo, CategoryEdit},
+    database_info::{DatabaseInfo, FoundDatabase},
+    device_info::{CardInfo, RomVersion, SystemInfo, UserInfo},
     error::{ConduitError, SyncManagerError},
+    mirror::DesktopMirror,
+    palm_time::{from_palm_local, to_palm_local},
+    pdb_file::{self, file_name, info_blocks, DbEntries, InfoBlocks},
+    record_sync::{
+        attributes_from_raw, attributes_to_raw, merge, overwrite, ConflictPolicy, DesktopStore,
+        MergeResult, RecordChange, RecordDiff, SyncRecord,
+    },
     syncmgr_extern::{
-        eDbOpenModes, openDatabaseHandle, CDbCreateDB, CRawPreferenceInfo, CRawRecordInfo,
-        SyncMgrApi, CONDHANDLE, DB_NAMELEN,
+        eDbFlags, eDbOpenModes, eSyncTypes, openDatabaseHandle, CCallAppParams, CCallModuleParams,
+        CCardInfo, CDbCreateDB, CDbGenInfo, CDbList, CRawPreferenceInfo, CRawRecordInfo,
+        CSyncProperties, CSystemInfo, CUserIDInfo, SyncDatabaseInfoType, SyncFindDbByNameParams,
+        SyncFindDbByTypeCreatorParams, SyncMgrApi, SyncReadOpenDbInfoParams, CONDHANDLE,
+        DB_NAMELEN, SYNC_DB_INFO_OPT_GET_ATTRIBUTES, SYNC_DB_INFO_OPT_GET_MAX_REC_SIZE,
+        SYNC_DB_INFO_OPT_GET_SIZE, SYNC_DB_SRCH_OPT_NEW_SEARCH,
     },
     ConduitManager,
 };
@@ -33,6 +54,14 @@ fn uchars_to_u32(creator: [c_uchar; 4]) -> u32 {
     u32::from_be_bytes(creator)
 }
 
+/// Resolve a path relative to the HotSync user folder, if known
+fn in_user_dir(user_dir: &Option<PathBuf>, path: PathBuf) -> PathBuf {
+    match user_dir {
+        Some(user_dir) => user_dir.join(path),
+        None => path,
+    }
+}
+
 pub trait DatabaseGenerator {
     fn generate(self: Box<Self>) -> (CString, [c_uchar; 4], PalmDatabase<PdbDatabase>);
 }
@@ -42,54 +71,301 @@ pub enum PreferenceType<T> {
     Dynamic(u16, Box<dyn Fn(Option<T>) -> Option<T>>),
 }
 
-/// Need the database name (with no extension), the type code, and the db itself
+/// Need the database name (with no extension), the type code, and the db itself. Databases with
+/// the resource flag in their header (such as a .prc) are created as resource databases on the
+/// handheld
 pub enum ConduitDBSource {
+    /// Write this database. No AppInfo or SortInfo block is written, so the handheld database has
+    /// no category names; wrap it in `WithInfoBlocks` to add them
     Static(CString, [c_uchar; 4], PalmDatabase<PdbDatabase>),
+    /// Write the .pdb or .prc file at this path, along with its AppInfo and SortInfo blocks
     File(CString, [c_uchar; 4], std::path::PathBuf),
+    /// Write the database the generator returns. Like `Static`, no AppInfo or SortInfo block is
+    /// written unless wrapped in `WithInfoBlocks`
     Generator(Box<dyn DatabaseGenerator>),
-    // add a way to pass modified/deleted recs here and update a db
+    /// Write each record under the unique ID it has in the source database, instead of letting the
+    /// handheld assign new ones
+    KeepUniqueIds(Box<ConduitDBSource>),
+    /// Write these AppInfo and SortInfo blocks along with the database. Blocks are read from the
+    /// file automatically for `File` sources
+    WithInfoBlocks(Box<ConduitDBSource>, InfoBlocks),
+    /// Apply these changes to the database already on the handheld instead of replacing it. The
+    /// database is only created if missing
+    Incremental(CString, [c_uchar; 4], RecordDiff),
+    /// Write the database to this memory card instead of card 0
+    OnCard(Box<ConduitDBSource>, u8),
+    /// Store each payload, keyed by blob ID, as numbered chunk records so it can be larger than a
+    /// single record. Read them back with `ConduitDBSink::Blobs`
+    Blobs(CString, [c_uchar; 4], Vec<(u16, Vec<u8>)>),
 }
 
 /// Need the database name (with no extension), the type code, and the db itself
 pub enum ConduitDBSink {
     Dynamic(WorkOnDbType),
-    // add a way to pass modified/deleted recs here and update a db
+    /// Download every resource from a resource database
+    Resources(WorkOnResourcesType),
+    /// Like `Dynamic`, but also receive the AppInfo and SortInfo blocks
+    DynamicWithInfo(WorkOnDbWithInfoType),
+    /// Receive only the records added, modified, or deleted since the last sync. The database is
+    /// always left on the handheld, with its sync flags reset so the next sync only sees newer
+    /// changes
+    Changes(WorkOnChangesType),
+    /// Save the database as a .pdb or .prc file in this folder, relative to the user folder from
+    /// `ConduitBuilder::with_sync_properties`. The file is named after the database
+    File(PathBuf),
+    /// Download the database from this memory card instead of card 0
+    OnCard(Box<ConduitDBSink>, u8),
+    /// Read only the records with these unique IDs, keyed by ID, instead of the whole database.
+    /// IDs that aren't on the handheld are left out, and the database is always kept. See also
+    /// `SyncSession::read_records_by_id`
+    ById(Vec<u32>, WorkOnRecordsByIdType),
+    /// Reassemble the blobs written by `ConduitDBSource::Blobs`, keyed by blob ID. Fails if any
+    /// chunk is missing or corrupt
+    Blobs(WorkOnBlobsType),
+}
+
+impl ConduitDBSink {
+    /// Download the database from this memory card instead of card 0
+    pub fn on_card(self, card_no: u8) -> Self {
+        Self::OnCard(Box::new(self), card_no)
+    }
+
+    /// What to do with the database once downloaded. Some sinks only make sense with one choice
+    fn after_download(&self, requested: AfterDownload) -> AfterDownload {
+        match self {
+            ConduitDBSink::Changes(_) => AfterDownload::KeepAndResetFlags,
+            ConduitDBSink::ById(..) => AfterDownload::Keep,
+            _ => requested,
+        }
+    }
+
+    /// Split off the card to download from
+    fn into_card(self) -> (Self, u8) {
+        match self {
+            ConduitDBSink::OnCard(inner, card_no) => (inner.into_card().0, card_no),
+            sink => (sink, 0),
+        }
+    }
+}
+
+/// What to do with a database on the handheld once it has been downloaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AfterDownload {
+    /// Remove the database, for one-shot outboxes
+    #[default]
+    Delete,
+    /// Leave the database as it is
+    Keep,
+    /// Leave the database, but purge deleted records and clear the dirty flags
+    KeepAndResetFlags,
 }
 
 impl ConduitDBSource {
-    fn load_db_from_path(path: std::path::PathBuf) -> PalmDatabase<PdbDatabase> {
+    fn load_db_from_path(path: std::path::PathBuf) -> (PalmDatabase<PdbDatabase>, InfoBlocks) {
         let file_contents = std::fs::read(path).unwrap();
         let db = PalmDatabase::<PdbDatabase>::from_bytes(&file_contents).unwrap();
-        db
+        (db, info_blocks(&file_contents))
     }
 
-    fn get_db(self) -> (CString, u32, PalmDatabase<PdbDatabase>) {
-        match self {
-            ConduitDBSource::Static(name, arr, db) => (name, uchars_to_u32(arr), db),
+    /// Keep the unique IDs of the records in this database when writing them to the handheld.
+    /// Needed when applications on the handheld refer to records by ID
+    pub fn keep_unique_ids(self) -> Self {
+        Self::KeepUniqueIds(Box::new(self))
+    }
+
+    /// Write these AppInfo and SortInfo blocks along with the database
+    pub fn with_info_blocks(self, info: InfoBlocks) -> Self {
+        Self::WithInfoBlocks(Box::new(self), info)
+    }
+
+    /// Write the database to this memory card instead of card 0
+    pub fn on_card(self, card_no: u8) -> Self {
+        Self::OnCard(Box::new(self), card_no)
+    }
+
+    fn get_db(self) -> SourceDb {
+        let (name, arr, db, info) = match self {
+            ConduitDBSource::Incremental(name, arr, diff) => {
+                return SourceDb {
+                    name,
+                    ty: uchars_to_u32(arr),
+                    contents: SourceContents::Changes(diff),
+                    info: InfoBlocks::default(),
+                    resource: false,
+                    keep_ids: false,
+                    card_no: 0,
+                }
+            }
+            ConduitDBSource::Blobs(name, arr, blobs) => {
+                return SourceDb {
+                    name,
+                    ty: uchars_to_u32(arr),
+                    contents: SourceContents::Blobs(blobs),
+                    info: InfoBlocks::default(),
+                    resource: false,
+                    keep_ids: false,
+                    card_no: 0,
+                }
+            }
+            ConduitDBSource::Static(name, arr, db) => (name, arr, db, InfoBlocks::default()),
             ConduitDBSource::File(name, arr, path) => {
-                (name, uchars_to_u32(arr), Self::load_db_from_path(path))
+                let (db, info) = Self::load_db_from_path(path);
+                (name, arr, db, info)
             }
             ConduitDBSource::Generator(execute) => {
                 let (name, arr, db) = execute.generate();
-                (name, uchars_to_u32(arr), db)
+                (name, arr, db, InfoBlocks::default())
+            }
+            ConduitDBSource::KeepUniqueIds(inner) => {
+                return SourceDb {
+                    keep_ids: true,
+                    ..inner.get_db()
+                }
+            }
+            ConduitDBSource::WithInfoBlocks(inner, info) => {
+                return SourceDb {
+                    info,
+                    ..inner.get_db()
+                }
+            }
+            ConduitDBSource::OnCard(inner, card_no) => {
+                return SourceDb {
+                    card_no,
+                    ..inner.get_db()
+                }
             }
+        };
+        let resource = eDbFlags::from_bits_truncate(u32::from(db.header.attributes))
+            .contains(eDbFlags::eResource);
+        SourceDb {
+            name,
+            ty: uchars_to_u32(arr),
+            contents: SourceContents::Full(db),
+            info,
+            resource,
+            keep_ids: false,
+            card_no: 0,
+        }
+    }
+}
+
+/// What to write to a database on the handheld
+enum SourceContents {
+    /// Replace the whole database
+    Full(PalmDatabase<PdbDatabase>),
+    /// Update the existing database in place
+    Changes(RecordDiff),
+    /// Replace the whole database with the chunk records of these blobs
+    Blobs(Vec<(u16, Vec<u8>)>),
+}
+
+/// A database ready to be written to the handheld
+struct SourceDb {
+    name: CString,
+    ty: u32,
+    contents: SourceContents,
+    info: InfoBlocks,
+    resource: bool,
+    keep_ids: bool,
+    card_no: u8,
+}
+
+/// A database kept in sync record-by-record with a desktop copy
+struct RecordSyncTask {
+    name: CString,
+    card_no: u8,
+    ty: u32,
+    store: Box<dyn DesktopStore>,
+    policy: ConflictPolicy,
+}
+
+/// Which way data may flow during a sync, based on the sync type HotSync requested
+#[derive(Clone, Copy, PartialEq)]
+enum SyncDirection {
+    Both,
+    /// "Handheld overwrites Desktop"
+    HandheldToDesktop,
+    /// "Desktop overwrites Handheld"
+    DesktopToHandheld,
+    /// Copy data to the desktop without changing anything on the handheld
+    Backup,
+}
+
+impl SyncDirection {
+    /// `None` if the conduit shouldn't do anything this sync
+    fn from_sync_type(sync_type: eSyncTypes) -> Option<Self> {
+        match sync_type {
+            // install syncs only install files, the records are left for the next real sync
+            eSyncTypes::eDoNothing | eSyncTypes::eInstall | eSyncTypes::eProfileInstall => None,
+            eSyncTypes::eHHtoPC => Some(Self::HandheldToDesktop),
+            eSyncTypes::ePCtoHH => Some(Self::DesktopToHandheld),
+            eSyncTypes::eBackup => Some(Self::Backup),
+            _ => Some(Self::Both),
         }
     }
+
+    fn pushes(self) -> bool {
+        matches!(self, Self::Both | Self::DesktopToHandheld)
+    }
+
+    fn pulls(self) -> bool {
+        !matches!(self, Self::DesktopToHandheld)
+    }
 }
 
 type WorkOnDbType = Box<
     dyn FnMut(Vec<(Vec<u8>, RecordAttributes, u32)>) -> Result<(), Box<dyn Error + Sync + Send>>,
 >;
 
+type WorkOnDbWithInfoType = Box<
+    dyn FnMut(
+        Vec<(Vec<u8>, RecordAttributes, u32)>,
+        InfoBlocks,
+    ) -> Result<(), Box<dyn Error + Sync + Send>>,
+>;
+
+type WorkOnChangesType =
+    Box<dyn FnMut(Vec<RecordChange>) -> Result<(), Box<dyn Error + Sync + Send>>>;
+
+type WorkOnRecordsByIdType =
+    Box<dyn FnMut(HashMap<u32, RecordChange>) -> Result<(), Box<dyn Error + Sync + Send>>>;
+
+type WorkOnBlobsType =
+    Box<dyn FnMut(BTreeMap<u16, Vec<u8>>) -> Result<(), Box<dyn Error + Sync + Send>>>;
+
+/// What the handheld filled in when reading a record or resource
+struct RawRead {
+    /// The unique ID of a record, or the type of a resource
+    id: u32,
+    attributes: u8,
+    category: i16,
+    resource_id: u16,
+    data: Vec<u8>,
+}
+
+/// The data, type, and ID of a resource
+type RawResource = (Vec<u8>, [c_uchar; 4], u16);
+
+type WorkOnResourcesType =
+    Box<dyn FnMut(Vec<RawResource>) -> Result<(), Box<dyn Error + Sync + Send>>>;
+
 pub struct ConduitBuilder<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>> = Vec<u8>> {
     name: CString,
     creator_id: u32,
     create_if_not_exists: Vec<ConduitDBSource>,
     overwrite: Vec<ConduitDBSource>,
 
-    to_remove: Vec<CString>,
-    to_download: Vec<(CString, ConduitDBSink)>,
+    to_remove: Vec<(CString, u8)>,
+    to_download: Vec<(CString, ConduitDBSink, AfterDownload)>,
+    to_sync: Vec<RecordSyncTask>,
+    category_edits: Vec<(CString, u8, Vec<CategoryEdit>)>,
+    backup_dir: Option<(PathBuf, u8)>,
+    restore_from: Option<(PathBuf, RestoreOptions)>,
+    clock_sync: Option<(i32, Duration)>,
+    app_calls: Vec<AppCall>,
     preferences: Option<PreferenceType<Preferences>>,
+    sync_type: eSyncTypes,
+    user_dir: Option<PathBuf>,
 }
 
 impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> ConduitBuilder<Preferences> {
@@ -102,19 +378,45 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> ConduitBuilder<Preference
             overwrite: Vec::new(),
             to_remove: Vec::new(),
             to_download: Vec::new(),
+            to_sync: Vec::new(),
+            category_edits: Vec::new(),
+            backup_dir: None,
+            restore_from: None,
+            clock_sync: None,
+            app_calls: Vec::new(),
             preferences: None,
+            sync_type: eSyncTypes::eFast,
+            user_dir: None,
         }
     }
 
     /// Remove a database from the handheld, if it exists
     pub fn remove_db(mut self, to_remove: CString) -> Self {
-        self.to_remove.push(to_remove);
+        self.to_remove.push((to_remove, 0));
+        self
+    }
+
+    /// Remove a database from a memory card other than card 0, if it exists
+    pub fn remove_db_from_card(mut self, to_remove: CString, card_no: u8) -> Self {
+        self.to_remove.push((to_remove, card_no));
         self
     }
 
-    /// Download the records from a database on the handheld
-    pub fn download_db_and(mut self, to_download: CString, do_work: ConduitDBSink) -> Self {
-        self.to_download.push((to_download, do_work));
+    /// Download the records from a database on the handheld, then delete it. `ConduitDBSink::Changes`
+    /// keeps the database instead
+    pub fn download_db_and(self, to_download: CString, do_work: ConduitDBSink) -> Self {
+        self.download_db_then(to_download, do_work, AfterDownload::default())
+    }
+
+    /// Download the records from a database on the handheld, then delete or keep it.
+    /// `ConduitDBSink::Changes` always keeps the database and resets its sync flags
+    pub fn download_db_then(
+        mut self,
+        to_download: CString,
+        do_work: ConduitDBSink,
+        after: AfterDownload,
+    ) -> Self {
+        self.to_download.push((to_download, do_work, after));
         self
     }
 
@@ -124,12 +426,110 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> ConduitBuilder<Preference
         self
     }
 
-    /// Add a database iff not present on the handheld
+    /// Add a database iff not present on the handheld. `ConduitDBSource::Incremental` changes are
+    /// always applied, creating the database first if needed
     pub fn create_db(mut self, source: ConduitDBSource) -> Self {
         self.create_if_not_exists.push(source);
         self
     }
 
+    /// Keep a database in sync with a desktop copy, exchanging only the records modified on either
+    /// side since the last sync. The database is created with the given type code if not present
+    pub fn sync_db(
+        self,
+        name: CString,
+        ty: [c_uchar; 4],
+        store: Box<dyn DesktopStore>,
+        policy: ConflictPolicy,
+    ) -> Self {
+        self.sync_db_on_card(name, 0, ty, store, policy)
+    }
+
+    /// Like `sync_db`, for a database on a memory card other than card 0
+    pub fn sync_db_on_card(
+        mut self,
+        name: CString,
+        card_no: u8,
+        ty: [c_uchar; 4],
+        store: Box<dyn DesktopStore>,
+        policy: ConflictPolicy,
+    ) -> Self {
+        self.to_sync.push(RecordSyncTask {
+            name,
+            card_no,
+            ty: uchars_to_u32(ty),
+            store,
+            policy,
+        });
+        self
+    }
+
+    /// Change the categories of a database on the handheld, in order. Records in removed
+    /// categories are moved or purged as described by each edit
+    pub fn edit_categories(self, db_name: CString, edits: Vec<CategoryEdit>) -> Self {
+        self.edit_categories_on_card(db_name, 0, edits)
+    }
+
+    /// Like `edit_categories`, for a database on a memory card other than card 0
+    pub fn edit_categories_on_card(
+        mut self,
+        db_name: CString,
+        card_no: u8,
+        edits: Vec<CategoryEdit>,
+    ) -> Self {
+        self.category_edits.push((db_name, card_no, edits));
+        self
+    }
+
+    /// Save every RAM database on the handheld as a .pdb or .prc file in this folder, relative to
+    /// the user folder from `with_sync_properties`. Databases that haven't changed since the last
+    /// backup to the folder are skipped
+    pub fn backup_to(self, dir: PathBuf) -> Self {
+        self.backup_card_to(0, dir)
+    }
+
+    /// Like `backup_to`, for the RAM databases on a memory card other than card 0. Use a
+    /// different folder for each card
+    pub fn backup_card_to(mut self, card_no: u8, dir: PathBuf) -> Self {
+        self.backup_dir = Some((dir, card_no));
+        self
+    }
+
+    /// Install every .pdb and .prc file in this folder on the handheld, keeping their header flags,
+    /// version, record attributes, and unique IDs. The folder is relative to the user folder from
+    /// `with_sync_properties`, as with `backup_to`
+    pub fn restore_from(mut self, dir: PathBuf, options: RestoreOptions) -> Self {
+        self.restore_from = Some((dir, options));
+        self
+    }
+
+    /// Set the handheld clock from the PC when the two differ by more than `max_drift`. The
+    /// handheld keeps local time, `utc_offset` seconds east of UTC
+    pub fn sync_clock(mut self, utc_offset: i32, max_drift: Duration) -> Self {
+        self.clock_sync = Some((utc_offset, max_drift));
+        self
+    }
+
+    /// Send a launch code and parameter block to an application on the handheld, once the
+    /// databases have been pushed. Calls run in the order they are added. A parameter block over
+    /// `MAX_RECORD_SIZE` fails the sync when its call is reached
+    pub fn call_app(mut self, creator: [c_uchar; 4], action_code: u16, params: Vec<u8>) -> Self {
+        self.app_calls.push(AppCall {
+            creator,
+            action_code,
+            params,
+        });
+        self
+    }
+
+    /// Use the sync type and user folder HotSync passed to `OpenConduit`. Without this the conduit
+    /// always runs a fast sync and has no user folder
+    pub fn with_sync_properties(mut self, props: &CSyncProperties) -> Self {
+        self.sync_type = props.get_sync_type();
+        self.user_dir = props.get_dir_path();
+        self
+    }
+
     /// Set the preferences for the application
     pub fn set_preferences(mut self, source: PreferenceType<Preferences>) -> Self {
         self.preferences = Some(source);
@@ -145,8 +545,15 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> ConduitBuilder<Preference
             overwrite,
             to_remove,
             to_download,
+            to_sync,
+            category_edits,
+            backup_dir,
+            restore_from,
+            clock_sync,
+            app_calls,
             preferences,
-            ..
+            sync_type,
+            user_dir,
         } = self;
         Conduit {
             name,
@@ -158,7 +565,15 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> ConduitBuilder<Preference
             overwrite: overwrite.into_iter().map(ConduitDBSource::get_db).collect(),
             to_remove,
             to_download,
+            to_sync,
+            category_edits,
+            backup_dir,
+            restore_from,
+            clock_sync,
+            app_calls,
             preferences,
+            sync_type,
+            user_dir,
         }
     }
 }
@@ -166,12 +581,20 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> ConduitBuilder<Preference
 pub struct Conduit<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>> = Vec<u8>> {
     name: CString,
     creator_id: u32,
-    create_if_not_exists: Vec<(CString, u32, PalmDatabase<PdbDatabase>)>,
-    overwrite: Vec<(CString, u32, PalmDatabase<PdbDatabase>)>,
+    create_if_not_exists: Vec<SourceDb>,
+    overwrite: Vec<SourceDb>,
 
-    to_remove: Vec<CString>,
-    to_download: Vec<(CString, ConduitDBSink)>,
+    to_remove: Vec<(CString, u8)>,
+    to_download: Vec<(CString, ConduitDBSink, AfterDownload)>,
+    to_sync: Vec<RecordSyncTask>,
+    category_edits: Vec<(CString, u8, Vec<CategoryEdit>)>,
+    backup_dir: Option<(PathBuf, u8)>,
+    restore_from: Option<(PathBuf, RestoreOptions)>,
+    clock_sync: Option<(i32, Duration)>,
+    app_calls: Vec<AppCall>,
     preferences: Option<PreferenceType<Preferences>>,
+    sync_type: eSyncTypes,
+    user_dir: Option<PathBuf>,
 }
 
 impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> Conduit<Preferences> {
@@ -180,7 +603,7 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> Conduit<Preferences> {
         creator: u32,
         pref_id: u16,
     ) -> Result<Option<Preferences>, ConduitError> {
-        let mut bytes = vec![0_u8; 1024];
+        let mut bytes = vec![0_u8; sync.read_buffer_len(None)];
 
         let mut to_fill = MaybeUninit::new(CRawPreferenceInfo::new_with_buffer(
             0, creator, pref_id, &mut bytes,
@@ -235,50 +658,313 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> Conduit<Preferences> {
 
     fn read_rec_by_index(
         index: u16,
-        size_estimate: Option<u16>,
+        size_estimate: Option<usize>,
         handle: openDatabaseHandle,
         sync: &SyncSession,
     ) -> Result<(Vec<u8>, RecordAttributes, u32), ConduitError> {
-        let size = size_estimate.unwrap_or(1024);
-        let mut bytes = vec![0_u8; size as usize];
+        let record = sync.read_raw_rec(
+            size_estimate,
+            |bytes| CRawRecordInfo::new_for_reading_by_index(handle, index, bytes),
+            |rec| unsafe { sync.api.SyncReadRecordByIndex(rec) },
+        )?;
+        let attribs = attributes_from_raw(record.attributes, record.category);
+        Ok((record.data, attribs, record.id))
+    }
+
+    /// Read the next record modified on the handheld since the last sync, or `None` once all
+    /// have been read
+    fn read_next_modified_rec(
+        handle: openDatabaseHandle,
+        sync: &SyncSession,
+    ) -> Result<Option<RecordChange>, ConduitError> {
+        let mut bytes = vec![0_u8; sync.read_buffer_len(None)];
 
         let mut to_fill = MaybeUninit::new(CRawRecordInfo::new_for_reading_by_index(
-            handle, index, &mut bytes,
+            handle, 0, &mut bytes,
         ));
-        let mut ret_val = unsafe { sync.api.SyncReadRecordByIndex(to_fill.as_mut_ptr()) };
+        let ret_val = unsafe { sync.api.SyncReadNextModifiedRec(to_fill.as_mut_ptr()) };
 
-        // retry with the correct buffer size if too small
-        if ret_val == SyncManagerError::SYNCERR_LOCAL_BUFF_TOO_SMALL {
-            let new_size = unsafe { to_fill.assume_init_ref().get_size() } as usize;
-            bytes.resize(new_size, 0_u8);
-            to_fill = MaybeUninit::new(CRawRecordInfo::new_for_reading_by_index(
-                handle, index, &mut bytes,
-            ));
-            ret_val = unsafe { sync.api.SyncReadRecordByIndex(to_fill.as_mut_ptr()) };
+        match ret_val {
+            SyncManagerError::SYNCERR_NOT_FOUND => Ok(None),
+            // the handheld has already moved on to the next modified record, so read this one
+            // again by its id rather than retrying
+            SyncManagerError::SYNCERR_LOCAL_BUFF_TOO_SMALL => {
+                let record = unsafe { to_fill.assume_init_ref() };
+                let (id, size) = (record.get_id(), record.get_record_size());
+                sync.read_rec_by_id(id, Some(size as usize), handle)
+                    .map(Some)
+            }
+            SyncManagerError::SYNCERR_NONE => {
+                let record = unsafe { to_fill.assume_init() };
+                bytes.truncate(record.get_record_size() as usize);
+                Ok(Some(RecordChange::from_raw(
+                    record.get_id(),
+                    record.get_attributes(),
+                    record.get_category(),
+                    bytes,
+                )))
+            }
+            e => Err(ConduitError::Sync(e)),
         }
-        return_iff_conduit_err!(ret_val);
+    }
 
-        let record = unsafe { to_fill.assume_init() };
-        let attribs: RecordAttributes = record.get_attributes().into();
-        let id = record.get_id();
-        Ok((bytes, attribs, id))
+    /// Read every record in the database, skipping any the handheld reports as deleted
+    fn read_all_recs(
+        handle: openDatabaseHandle,
+        sync: &SyncSession,
+    ) -> Result<Vec<SyncRecord>, ConduitError> {
+        let rec_count = Self::get_db_rec_count(handle, sync)?;
+        let max_size = sync.max_record_size(handle);
+        let mut ret = Vec::with_capacity(rec_count as usize);
+        for record_index in 0..rec_count {
+            match Self::read_rec_by_index(record_index as u16, max_size, handle, sync) {
+                Ok((data, attributes, id)) => ret.push(SyncRecord {
+                    id,
+                    attributes,
+                    data,
+                }),
+                Err(ConduitError::Sync(SyncManagerError::SYNCERR_RECORD_DELETED)) => (),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(ret)
+    }
+
+    fn read_modified_recs(
+        handle: openDatabaseHandle,
+        sync: &SyncSession,
+    ) -> Result<Vec<RecordChange>, ConduitError> {
+        let mut ret = Vec::new();
+        while let Some(change) = Self::read_next_modified_rec(handle, sync)? {
+            ret.push(change);
+        }
+        Ok(ret)
+    }
+
+    /// Write a single record, returning the unique ID the handheld stored it under
+    fn write_rec(
+        handle: openDatabaseHandle,
+        record: SyncRecord,
+        sync: &SyncSession,
+    ) -> Result<u32, ConduitError> {
+        let SyncRecord {
+            id,
+            attributes,
+            mut data,
+        } = record;
+        let (flags, category) = attributes_to_raw(attributes);
+
+        let mut rec = CRawRecordInfo::new_for_writing(handle, flags, category, None, &mut data)?
+            .with_record_id(id);
+        unsafe {
+            return_iff_conduit_err!(sync.api.SyncWriteRec(&mut rec as *mut CRawRecordInfo));
+        }
+        Ok(rec.get_id())
+    }
+
+    /// Write each blob as its chunk records
+    fn write_blobs(
+        handle: openDatabaseHandle,
+        blobs: Vec<(u16, Vec<u8>)>,
+        sync: &SyncSession,
+    ) -> Result<(), ConduitError> {
+        for (blob_id, data) in blobs {
+            for chunk in split_blob(blob_id, &data)? {
+                let record = SyncRecord {
+                    id: 0,
+                    attributes: RecordAttributes::from(0),
+                    data: chunk,
+                };
+                Self::write_rec(handle, record, sync)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn delete_rec(
+        handle: openDatabaseHandle,
+        id: u32,
+        sync: &SyncSession,
+    ) -> Result<(), ConduitError> {
+        let rec = CRawRecordInfo::new_for_deleting(handle, id);
+        match unsafe { sync.api.SyncDeleteRec(&rec as *const CRawRecordInfo) } {
+            SyncManagerError::SYNCERR_NONE | SyncManagerError::SYNCERR_NOT_FOUND => Ok(()),
+            e => Err(ConduitError::Sync(e)),
+        }
+    }
+
+    /// Apply merged changes to an open database, returning the desktop records that were written
+    /// along with the unique IDs they now have on the handheld
+    fn apply_changes(
+        handle: openDatabaseHandle,
+        changes: Vec<RecordChange>,
+        sync: &SyncSession,
+    ) -> Result<Vec<(SyncRecord, u32)>, ConduitError> {
+        let mut assigned = Vec::new();
+        for change in changes {
+            match change {
+                RecordChange::Modified(record) => {
+                    let new_id = Self::write_rec(handle, record.clone(), sync)?;
+                    assigned.push((record, new_id));
+                }
+                RecordChange::Deleted(id) | RecordChange::Archived(SyncRecord { id, .. }) => {
+                    Self::delete_rec(handle, id, sync)?
+                }
+            }
+        }
+        Ok(assigned)
+    }
+
+    /// Two-way sync of a single database. A slow sync compares every record on the handheld
+    /// against the desktop mirror instead of trusting the handheld's dirty flags
+    fn sync_records(
+        task: RecordSyncTask,
+        creator_id: u32,
+        direction: SyncDirection,
+        slow: bool,
+        mirror_path: Option<PathBuf>,
+        sync: &SyncSession,
+    ) -> Result<(), ConduitError> {
+        let RecordSyncTask {
+            name,
+            card_no,
+            ty,
+            mut store,
+            policy,
+        } = task;
+
+        // only a sync that may change the handheld creates a missing database
+        let handle = if direction.pushes() {
+            Self::open_or_create_db(name.clone(), creator_id, ty, card_no, sync)?
+        } else {
+            // a backup leaves the sync flags alone, so it never writes
+            let opened = if direction == SyncDirection::Backup {
+                sync.open_db_read_only(name.clone(), card_no)
+            } else {
+                sync.open_db(name.clone(), card_no)
+            };
+            match opened {
+                Err(ConduitError::Sync(SyncManagerError::SYNCERR_NOT_FOUND)) => {
+                    sync.log_to_hs_log(
+                        CString::new(format!(
+                            "{} is not on the handheld, skipping\n",
+                            String::from_utf8_lossy(name.as_bytes())
+                        ))
+                        .unwrap(),
+                    )?;
+                    return Ok(());
+                }
+                res => res?,
+            }
+        };
+        sync.closing(handle, || {
+            let mut mirror = match &mirror_path {
+                Some(path) => DesktopMirror::load(path)?,
+                None => DesktopMirror::default(),
+            };
+            let handheld = if slow {
+                mirror.diff(Self::read_all_recs(handle, sync)?)
+            } else {
+                Self::read_modified_recs(handle, sync)?
+            };
+
+            let desktop = store.desktop_changes()?;
+            let merged = match direction {
+                SyncDirection::Both => merge(handheld.clone(), desktop, policy),
+                SyncDirection::HandheldToDesktop | SyncDirection::Backup => MergeResult {
+                    to_handheld: Vec::new(),
+                    to_desktop: overwrite(handheld.clone(), desktop, |id| mirror.get(id)),
+                },
+                SyncDirection::DesktopToHandheld => MergeResult {
+                    to_handheld: overwrite(desktop, handheld.clone(), |id| mirror.get(id)),
+                    to_desktop: Vec::new(),
+                },
+            };
+            mirror.apply(&handheld);
+            sync.log_to_hs_log(
+                CString::new(format!(
+                    "{} sync of {}: {} change(s) to handheld, {} change(s) to desktop\n",
+                    if slow { "Slow" } else { "Fast" },
+                    String::from_utf8_lossy(name.as_bytes()),
+                    merged.to_handheld.len(),
+                    merged.to_desktop.len(),
+                ))
+                .unwrap(),
+            )?;
+
+            mirror.apply(&merged.to_handheld);
+            let assigned = Self::apply_changes(handle, merged.to_handheld, sync)?;
+            for (record, new_id) in assigned.iter() {
+                mirror.record_written(record, *new_id);
+            }
+            store.apply_handheld_changes(merged.to_desktop)?;
+            store.ids_assigned(assigned)?;
+            if let Some(path) = &mirror_path {
+                mirror.save(path)?;
+            }
+
+            // a backup leaves the sync flags alone so the next real sync still sees the changes
+            if direction != SyncDirection::Backup {
+                Self::reset_sync_flags(handle, sync)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Drop deleted records and clear the dirty flags, once the desktop has seen every change
+    fn reset_sync_flags(
+        handle: openDatabaseHandle,
+        sync: &SyncSession,
+    ) -> Result<(), ConduitError> {
+        unsafe {
+            return_iff_conduit_err!(sync.api.SyncPurgeDeletedRecs(handle));
+            return_iff_conduit_err!(sync.api.SyncResetSyncFlags(handle));
+        }
+        Ok(())
+    }
+
+    /// Set the handheld clock from the PC if it has drifted too far
+    fn sync_clock(
+        utc_offset: i32,
+        max_drift: Duration,
+        sync: &SyncSession,
+    ) -> Result<(), ConduitError> {
+        let now = SystemTime::now();
+        let log_str = match sync.read_clock(utc_offset)? {
+            Some(handheld) => {
+                let drift = match handheld.duration_since(now) {
+                    Ok(ahead) => ahead,
+                    Err(behind) => behind.duration(),
+                };
+                if drift <= max_drift {
+                    return Ok(());
+                }
+                format!(
+                    "Handheld clock is off by {} seconds, setting it from the PC\n",
+                    drift.as_secs()
+                )
+            }
+            None => "Handheld clock was never set, setting it from the PC\n".to_string(),
+        };
+        sync.log_to_hs_log(CString::new(log_str).unwrap())?;
+        sync.write_clock(now, utc_offset)
     }
 
     fn get_db_rec_count(
         handle: openDatabaseHandle,
         sync: &SyncSession,
     ) -> Result<u32, ConduitError> {
-        let mut ret = MaybeUninit::new(0);
+        let mut ret = MaybeUninit::new(0_u16);
         unsafe {
             return_iff_conduit_err!(sync.api.SyncGetDBRecordCount(handle, ret.as_mut_ptr()));
-            Ok(ret.assume_init())
+            Ok(ret.assume_init() as u32)
         }
     }
 
-    fn remove_db(to_remove: CString, sync: &SyncSession) -> Result<(), ConduitError> {
+    fn remove_db(to_remove: CString, card_no: u8, sync: &SyncSession) -> Result<(), ConduitError> {
         let ret = unsafe {
             sync.api
-                .SyncDeleteDB(to_remove.as_bytes_with_nul().as_ptr(), 0)
+                .SyncDeleteDB(to_remove.as_bytes_with_nul().as_ptr(), card_no as c_int)
         };
         let log_str;
 
@@ -310,26 +996,18 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> Conduit<Preferences> {
         ret
     }
 
-    fn open_db(to_open: CString, sync: &SyncSession) -> Result<openDatabaseHandle, ConduitError> {
-        let mut handle = MaybeUninit::new(openDatabaseHandle::default());
-        let m_name = {
-            let mut name = [0; DB_NAMELEN];
-            for (idx, char) in to_open.into_bytes().into_iter().enumerate() {
-                if idx >= DB_NAMELEN - 1 {
-                    break;
-                }
-                name[idx] = char;
+    fn open_or_create_db(
+        name: CString,
+        creator_id: u32,
+        ty: u32,
+        card_no: u8,
+        sync: &SyncSession,
+    ) -> Result<openDatabaseHandle, ConduitError> {
+        match sync.open_db(name.clone(), card_no) {
+            Err(ConduitError::Sync(SyncManagerError::SYNCERR_NOT_FOUND)) => {
+                Self::create_db(name, creator_id, ty, false, card_no, sync)
             }
-            name
-        };
-        unsafe {
-            return_iff_conduit_err!(sync.api.SyncOpenDB(
-                m_name.as_ptr(),
-                0,
-                handle.as_mut_ptr(),
-                eDbOpenModes::eDbExclusive | eDbOpenModes::eDbRead | eDbOpenModes::eDbWrite
-            ));
-            Ok(handle.assume_init())
+            res => res,
         }
     }
 
@@ -338,15 +1016,61 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> Conduit<Preferences> {
         creator_id: u32,
         ty: u32,
         resource: bool,
+        card_no: u8,
+        sync: &SyncSession,
+    ) -> Result<openDatabaseHandle, ConduitError> {
+        Self::create_db_from(
+            CDbCreateDB::new(to_create.clone(), creator_id, ty, resource).with_card(card_no),
+            to_create,
+            sync,
+        )
+    }
+
+    /// Create the database for a source. `None` if the source should only be created when
+    /// missing and the database is already there. Sync Managers without the find calls can only
+    /// tell by refusing to create it
+    fn create_source_db(
+        name: CString,
+        creator_id: u32,
+        ty: u32,
+        resource: bool,
+        card_no: u8,
+        create_only: bool,
+        sync: &SyncSession,
+    ) -> Result<Option<openDatabaseHandle>, ConduitError> {
+        let already_exists = || {
+            sync.log_to_hs_log(
+                CString::new(format!(
+                    "{} already exists, not creating\n",
+                    String::from_utf8_lossy(name.as_bytes())
+                ))
+                .unwrap(),
+            )?;
+            Ok(None)
+        };
+        if create_only
+            && sync.capabilities.supports(Capability::FindDatabases)
+            && sync.find_db_by_name(card_no, &name)?.is_some()
+        {
+            return already_exists();
+        }
+        match Self::create_db(name.clone(), creator_id, ty, resource, card_no, sync) {
+            Err(ConduitError::Sync(SyncManagerError::SYNCERR_FILE_ALREADY_EXIST))
+                if create_only =>
+            {
+                already_exists()
+            }
+            created => created.map(Some),
+        }
+    }
+
+    fn create_db_from(
+        create: CDbCreateDB,
+        to_create: CString,
         sync: &SyncSession,
     ) -> Result<openDatabaseHandle, ConduitError> {
         let stats: CDbCreateDB;
-        let mut stats_init = MaybeUninit::new(CDbCreateDB::new(
-            to_create.clone(),
-            creator_id,
-            ty,
-            resource,
-        ));
+        let mut stats_init = MaybeUninit::new(create);
         unsafe {
             return_iff_conduit_err!(sync.api.SyncCreateDB(stats_init.as_mut_ptr()));
             stats = stats_init.assume_init()
@@ -364,12 +1088,13 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> Conduit<Preferences> {
         sync: &SyncSession,
     ) -> Result<Vec<(Vec<u8>, RecordAttributes, u32)>, ConduitError> {
         let rec_count = Self::get_db_rec_count(handle, sync)?;
+        let max_size = sync.max_record_size(handle);
         let mut ret = Vec::with_capacity(rec_count as usize);
 
         for record_index in 0..rec_count {
             ret.push(Self::read_rec_by_index(
                 record_index as u16,
-                None,
+                max_size,
                 handle,
                 sync,
             )?);
@@ -378,53 +1103,437 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> Conduit<Preferences> {
         Ok(ret)
     }
 
+    fn read_res_by_index(
+        index: u16,
+        size_estimate: Option<usize>,
+        handle: openDatabaseHandle,
+        sync: &SyncSession,
+    ) -> Result<RawResource, ConduitError> {
+        let resource = sync.read_raw_rec(
+            size_estimate,
+            |bytes| CRawRecordInfo::new_for_reading_by_index(handle, index, bytes),
+            |rec| unsafe { sync.api.SyncReadResRecordByIndex(rec, 1) },
+        )?;
+        // the handheld fills in the resource type in place of the unique ID
+        Ok((
+            resource.data,
+            resource.id.to_be_bytes(),
+            resource.resource_id,
+        ))
+    }
+
+    fn drain_resources(
+        handle: openDatabaseHandle,
+        sync: &SyncSession,
+    ) -> Result<Vec<RawResource>, ConduitError> {
+        let rsc_count = Self::get_db_rec_count(handle, sync)?;
+        let max_size = sync.max_record_size(handle);
+        (0..rsc_count)
+            .map(|index| Self::read_res_by_index(index as u16, max_size, handle, sync))
+            .collect()
+    }
+
     fn fill_db(
         handle: openDatabaseHandle,
         contents: PalmDatabase<PdbDatabase>,
+        keep_ids: bool,
         sync: &SyncSession,
     ) -> Result<(), ConduitError> {
         for (hdr, data) in contents.list_records_resources() {
             let mut data = data.clone();
-            if let Some(mut attributes) = hdr.attributes() {
+            if let Some(attributes) = hdr.attributes() {
                 // normal record
-                let _category = attributes.category as i16;
-                attributes.category = 0;
-                let _flags = u8::from(attributes);
+                let (flags, category) = attributes_to_raw(attributes);
+                let id = if keep_ids {
+                    hdr.unique_id().unwrap_or(0)
+                } else {
+                    0
+                };
                 unsafe {
-                    let mut rec = CRawRecordInfo::new_for_writing(handle, 0, 0, None, &mut data);
+                    let mut rec =
+                        CRawRecordInfo::new_for_writing(handle, flags, category, None, &mut data)?
+                            .with_record_id(id);
                     return_iff_conduit_err!(sync.api.SyncWriteRec(&mut rec as *mut CRawRecordInfo));
                 }
             } else {
                 // yer a resource, harry
-                sync.log_to_hs_log(CString::new("Writing resource for some reason??\n").unwrap())?;
-                let rsc_ty = u32::from_be_bytes(
-                    hdr.name_str()
-                        .unwrap()
-                        .as_bytes()
-                        .to_vec()
-                        .try_into()
-                        .unwrap(),
-                );
-                let rsc_id = hdr.resource_id().unwrap();
-                unsafe {
-                    let rec = CRawRecordInfo::new_for_writing(
-                        handle,
-                        0,
-                        0,
-                        Some((rsc_ty, rsc_id)),
-                        &mut data,
-                    );
-                    return_iff_conduit_err!(sync.api.SyncWriteResourceRec(rec));
-                }
+                let rsc_ty: [c_uchar; 4] = hdr
+                    .name_str()
+                    .and_then(|name| name.as_bytes().try_into().ok())
+                    .ok_or(ConduitError::InvalidResource)?;
+                let rsc_id = hdr.resource_id().ok_or(ConduitError::InvalidResource)?;
+                Self::write_resource(handle, (data, rsc_ty, rsc_id), sync)?;
             }
         }
         Ok(())
     }
 
-    fn close_db(handle: openDatabaseHandle, sync: &SyncSession) -> Result<(), ConduitError> {
-        let log_str = format!("Closing database\n");
-        sync.log_to_hs_log(CString::new(log_str).unwrap())?;
-        return_iff_conduit_err!(unsafe { sync.api.SyncCloseDB(handle) });
+    fn write_resource(
+        handle: openDatabaseHandle,
+        resource: RawResource,
+        sync: &SyncSession,
+    ) -> Result<(), ConduitError> {
+        let (mut data, rsc_ty, rsc_id) = resource;
+        let mut rec = CRawRecordInfo::new_for_writing(
+            handle,
+            0,
+            0,
+            Some((uchars_to_u32(rsc_ty), rsc_id)),
+            &mut data,
+        )?;
+        return_iff_conduit_err!(unsafe {
+            sync.api
+                .SyncWriteResourceRec(&mut rec as *mut CRawRecordInfo)
+        });
+        Ok(())
+    }
+
+    /// Install the .pdb and .prc files in `dir` on the handheld
+    fn restore(
+        dir: &Path,
+        options: RestoreOptions,
+        sync: &SyncSession,
+    ) -> Result<(), ConduitError> {
+        let names = |ram: DbLocation| -> Result<HashSet<CString>, ConduitError> {
+            Ok(sync
+                .list_databases(options.card_no, ram)?
+                .into_iter()
+                .map(|info| info.name)
+                .collect())
+        };
+        let rom_names = if options.skip_rom_names {
+            names(DbLocation::Rom)?
+        } else {
+            HashSet::new()
+        };
+        let existing = names(DbLocation::Ram)?;
+
+        let mut paths = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    ext.eq_ignore_ascii_case("pdb") || ext.eq_ignore_ascii_case("prc")
+                })
+        });
+        paths.sort();
+
+        let mut restored = 0;
+        for path in paths {
+            let (info, blocks, entries) = pdb_file::from_bytes(&std::fs::read(&path)?)?;
+            let db_name = String::from_utf8_lossy(info.name.as_bytes()).into_owned();
+            if rom_names.contains(&info.name) {
+                sync.log_to_hs_log(
+                    CString::new(format!("Not restoring {db_name}, it is in ROM\n")).unwrap(),
+                )?;
+                continue;
+            }
+            if existing.contains(&info.name) {
+                if options.only_missing {
+                    continue;
+                }
+                Self::remove_db(info.name.clone(), options.card_no, sync)?;
+            }
+
+            let resource = matches!(entries, DbEntries::Resources(_));
+            let create = CDbCreateDB::new(
+                info.name.clone(),
+                uchars_to_u32(info.creator),
+                uchars_to_u32(info.db_type),
+                resource,
+            )
+            .with_flags(info.flags)
+            .with_version(info.version)
+            .with_card(options.card_no);
+            let handle = Self::create_db_from(create, info.name, sync)?;
+            sync.closing(handle, || {
+                match entries {
+                    DbEntries::Records(records) => {
+                        for (data, attributes, id) in records {
+                            let record = SyncRecord {
+                                id,
+                                attributes,
+                                data,
+                            };
+                            Self::write_rec(handle, record, sync)?;
+                        }
+                    }
+                    DbEntries::Resources(resources) => {
+                        for resource in resources {
+                            Self::write_resource(handle, resource, sync)?;
+                        }
+                    }
+                }
+                Self::write_info_blocks(handle, blocks, sync)
+            })?;
+            restored += 1;
+        }
+        sync.log_to_hs_log(CString::new(format!("Restored {restored} database(s)\n")).unwrap())?;
+        Ok(())
+    }
+
+    fn read_info_block(
+        handle: openDatabaseHandle,
+        sort_info: bool,
+        sync: &SyncSession,
+    ) -> Result<Option<Vec<u8>>, ConduitError> {
+        let read = |info: *mut CDbGenInfo| unsafe {
+            if sort_info {
+                sync.api.SyncReadDBSortInfoBlock(handle, info)
+            } else {
+                sync.api.SyncReadDBAppInfoBlock(handle, info)
+            }
+        };
+        let mut bytes = vec![0_u8; sync.read_buffer_len(None)];
+
+        let mut to_fill = MaybeUninit::new(CDbGenInfo::new_for_reading(&mut bytes));
+        let mut ret_val = read(to_fill.as_mut_ptr());
+
+        // retry with the correct buffer size if too small
+        let block_size = unsafe { to_fill.assume_init_ref().get_block_size() } as usize;
+        if matches!(
+            ret_val,
+            SyncManagerError::SYNCERR_NONE | SyncManagerError::SYNCERR_LOCAL_BUFF_TOO_SMALL
+        ) && block_size > bytes.len()
+        {
+            bytes.resize(block_size, 0_u8);
+            to_fill = MaybeUninit::new(CDbGenInfo::new_for_reading(&mut bytes));
+            ret_val = read(to_fill.as_mut_ptr());
+        }
+
+        if matches!(ret_val, SyncManagerError::SYNCERR_NOT_FOUND) {
+            return Ok(None);
+        }
+        return_iff_conduit_err!(ret_val);
+        let block_size = unsafe { to_fill.assume_init().get_block_size() } as usize;
+        bytes.truncate(block_size);
+        Ok(Some(bytes).filter(|bytes| !bytes.is_empty()))
+    }
+
+    /// Read the AppInfo and SortInfo blocks of an open database
+    fn read_info_blocks(
+        handle: openDatabaseHandle,
+        sync: &SyncSession,
+    ) -> Result<InfoBlocks, ConduitError> {
+        Ok(InfoBlocks {
+            app_info: Self::read_info_block(handle, false, sync)?,
+            sort_info: Self::read_info_block(handle, true, sync)?,
+        })
+    }
+
+    fn write_info_blocks(
+        handle: openDatabaseHandle,
+        info: InfoBlocks,
+        sync: &SyncSession,
+    ) -> Result<(), ConduitError> {
+        if let Some(mut app_info) = info.app_info {
+            let to_write = CDbGenInfo::new_for_writing(&mut app_info)?;
+            return_iff_conduit_err!(unsafe {
+                sync.api
+                    .SyncWriteDBAppInfoBlock(handle, &to_write as *const CDbGenInfo)
+            });
+        }
+        if let Some(mut sort_info) = info.sort_info {
+            let to_write = CDbGenInfo::new_for_writing(&mut sort_info)?;
+            return_iff_conduit_err!(unsafe {
+                sync.api
+                    .SyncWriteDBSortInfoBlock(handle, &to_write as *const CDbGenInfo)
+            });
+        }
+        Ok(())
+    }
+
+    /// Save each RAM database that changed since the last backup to `dir`. A database that can't
+    /// be saved is logged and left for the next backup, without stopping the rest
+    fn backup(dir: &Path, card_no: u8, sync: &SyncSession) -> Result<(), ConduitError> {
+        let mut manifest = BackupManifest::load(dir)?;
+        let (mut saved, mut skipped, mut failed) = (0, 0, 0);
+        for info in sync.list_databases(card_no, DbLocation::Ram)? {
+            if !manifest.needs_backup(&info) {
+                skipped += 1;
+                continue;
+            }
+            match Self::backup_db(&info, card_no, dir, sync) {
+                Ok(_) => {
+                    manifest.record(&info);
+                    saved += 1;
+                }
+                Err(e) => {
+                    failed += 1;
+                    sync.log_to_hs_log(
+                        CString::new(format!(
+                            "Could not back up {}: {e}\n",
+                            String::from_utf8_lossy(info.name.as_bytes())
+                        ))
+                        .unwrap(),
+                    )?;
+                }
+            }
+        }
+        manifest.save()?;
+        sync.log_to_hs_log(
+            CString::new(format!(
+                "Backed up {saved} database(s), {skipped} unchanged, {failed} failed\n"
+            ))
+            .unwrap(),
+        )?;
+        Ok(())
+    }
+
+    /// Save a single database to `dir`. Databases held open by a running application can't be
+    /// read
+    fn backup_db(
+        info: &DatabaseInfo,
+        card_no: u8,
+        dir: &Path,
+        sync: &SyncSession,
+    ) -> Result<(), ConduitError> {
+        let handle = sync.open_db_read_only(info.name.clone(), card_no)?;
+        sync.closing(handle, || Self::download_to_file(handle, info, dir, sync))
+            .map(|_| ())
+    }
+
+    /// The database list entry for a database, which holds everything a file header needs
+    fn listed_db_info(
+        name: &CString,
+        card_no: u8,
+        sync: &SyncSession,
+    ) -> Result<DatabaseInfo, ConduitError> {
+        for location in [DbLocation::Ram, DbLocation::Rom] {
+            let listed = sync.list_databases(card_no, location)?;
+            if let Some(info) = listed.into_iter().find(|info| info.name == *name) {
+                return Ok(info);
+            }
+        }
+        Err(ConduitError::Sync(SyncManagerError::SYNCERR_NOT_FOUND))
+    }
+
+    /// Write an open database to `dir` as a complete .pdb or .prc file, returning its path. The
+    /// header comes from the database's entry in the database list
+    fn download_to_file(
+        handle: openDatabaseHandle,
+        info: &DatabaseInfo,
+        dir: &Path,
+        sync: &SyncSession,
+    ) -> Result<PathBuf, ConduitError> {
+        let resource = info.flags.contains(eDbFlags::eResource);
+        let entries = if resource {
+            DbEntries::Resources(Self::drain_resources(handle, sync)?)
+        } else {
+            DbEntries::Records(Self::drain_db(handle, sync)?)
+        };
+        let blocks = Self::read_info_blocks(handle, sync)?;
+
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(file_name(&info.name, resource));
+        std::fs::write(&path, pdb_file::to_bytes(info, &blocks, &entries))?;
+        sync.log_to_hs_log(
+            CString::new(format!("Saved database to {}\n", path.display())).unwrap(),
+        )?;
+        Ok(path)
+    }
+
+    /// Hand the contents of an open database to a sink
+    fn download_into(
+        sink: ConduitDBSink,
+        name: &CString,
+        card_no: u8,
+        handle: openDatabaseHandle,
+        user_dir: &Option<PathBuf>,
+        sync: &SyncSession,
+    ) -> Result<(), ConduitError> {
+        match sink {
+            ConduitDBSink::Dynamic(mut op) => op(Self::drain_db(handle, sync)?)?,
+            ConduitDBSink::Resources(mut op) => op(Self::drain_resources(handle, sync)?)?,
+            ConduitDBSink::DynamicWithInfo(mut op) => op(
+                Self::drain_db(handle, sync)?,
+                Self::read_info_blocks(handle, sync)?,
+            )?,
+            ConduitDBSink::Changes(mut op) => op(Self::read_modified_recs(handle, sync)?)?,
+            ConduitDBSink::ById(ids, mut op) => op(sync.read_recs_by_id(&ids, handle)?)?,
+            ConduitDBSink::Blobs(mut op) => op(join_blobs(
+                Self::drain_db(handle, sync)?
+                    .into_iter()
+                    .map(|(data, _, _)| data),
+            )?)?,
+            ConduitDBSink::File(dir) => {
+                let info = Self::listed_db_info(name, card_no, sync)?;
+                Self::download_to_file(handle, &info, &in_user_dir(user_dir, dir), sync)?;
+            }
+            // the database was already opened on the card given here
+            ConduitDBSink::OnCard(inner, _) => {
+                Self::download_into(*inner, name, card_no, handle, user_dir, sync)?
+            }
+        }
+        Ok(())
+    }
+
+    fn edit_categories(
+        handle: openDatabaseHandle,
+        edits: Vec<CategoryEdit>,
+        sync: &SyncSession,
+    ) -> Result<(), ConduitError> {
+        /// What happens to the records of a removed category
+        enum RecordOp {
+            Move(u8, u8),
+            Purge(u8),
+        }
+
+        let app_info = Self::read_info_block(handle, false, sync)?;
+        let mut categories = CategoryAppInfo::from_bytes(&app_info.unwrap_or_default())?;
+        // apply every edit before touching any records, so a bad edit leaves the handheld as it was
+        let mut record_ops = Vec::new();
+        for edit in edits {
+            match edit {
+                CategoryEdit::Add(name) => {
+                    categories.add(name)?;
+                }
+                CategoryEdit::Rename(from, to) => {
+                    categories.rename(&from, to)?;
+                }
+                CategoryEdit::Merge(from, into) => {
+                    if from == into {
+                        return Err(ConduitError::MergeIntoSelf);
+                    }
+                    let into = categories
+                        .index_of(&into)
+                        .ok_or(ConduitError::NoSuchCategory)?;
+                    record_ops.push(RecordOp::Move(categories.remove(&from)?, into));
+                }
+                CategoryEdit::Delete(name) => {
+                    record_ops.push(RecordOp::Move(categories.remove(&name)?, 0));
+                }
+                CategoryEdit::Purge(name) => {
+                    record_ops.push(RecordOp::Purge(categories.remove(&name)?));
+                }
+            }
+        }
+        // write the names first, so a failure below leaves records in an unused category rather
+        // than under a name that was meant to be gone
+        Self::write_info_blocks(
+            handle,
+            InfoBlocks {
+                app_info: Some(categories.to_bytes()),
+                sort_info: None,
+            },
+            sync,
+        )?;
+        for op in record_ops {
+            match op {
+                RecordOp::Move(from, into) => {
+                    return_iff_conduit_err!(unsafe {
+                        sync.api.SyncChangeCategory(handle, from, into)
+                    });
+                }
+                RecordOp::Purge(idx) => {
+                    return_iff_conduit_err!(unsafe {
+                        sync.api.SyncPurgeAllRecsInCategory(handle, idx as i16)
+                    });
+                }
+            }
+        }
         Ok(())
     }
 }
@@ -433,9 +1542,16 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> Conduit<Preferences> {
     /// Execute the conduit tasks defined with `ConduitBuilder`
     pub fn sync(self) -> Result<(), ConduitError> {
         let ss = SyncSession::init()?;
+        let ret = self.sync_in(&ss);
+        ret.and(ss.shutdown())
+    }
+
+    /// Execute the conduit tasks within an existing session, such as one already used to
+    /// query the handheld
+    pub fn sync_in(self, ss: &SyncSession) -> Result<(), ConduitError> {
         let name = self.name.clone();
 
-        let ret = match self.sync_internal(&ss) {
+        match self.sync_internal(ss) {
             Ok(_) => {
                 let _ = ss.log_to_hs_log(CString::new("Sync completed!").unwrap());
                 Ok(())
@@ -450,8 +1566,7 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> Conduit<Preferences> {
                 let _ = ss.log_to_hs_log(CString::new(err_str).unwrap());
                 Err(e)
             }
-        };
-        ret.and(ss.shutdown())
+        }
     }
 
     fn sync_internal(self, ss: &SyncSession) -> Result<(), ConduitError> {
@@ -463,7 +1578,28 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> Conduit<Preferences> {
             .unwrap(),
         )?;
 
-        if let Some(pref) = self.preferences {
+        let Some(direction) = SyncDirection::from_sync_type(self.sync_type) else {
+            ss.log_to_hs_log(
+                CString::new(format!(
+                    "{} does nothing on a {:?} sync, skipping\n",
+                    String::from_utf8_lossy(self.name.as_bytes()),
+                    self.sync_type
+                ))
+                .unwrap(),
+            )?;
+            return Ok(());
+        };
+
+        // a backup never changes the handheld
+        if let Some((utc_offset, max_drift)) = self
+            .clock_sync
+            .filter(|_| direction != SyncDirection::Backup)
+        {
+            Self::sync_clock(utc_offset, max_drift, ss)?;
+        }
+
+        let preferences = self.preferences.filter(|_| direction.pushes());
+        if let Some(pref) = preferences {
             ss.log_to_hs_log(CString::new("Syncing preferences").unwrap())?;
             match pref {
                 PreferenceType::Static(id, pref) => {
@@ -482,70 +1618,731 @@ impl<Preferences: TryInto<Vec<u8>> + TryFrom<Vec<u8>>> Conduit<Preferences> {
             info!("No prefs to sync");
         }
 
-        for (to_drain, operation) in self.to_download {
-            let Ok(handle) = Self::open_db(to_drain.clone(), &ss) else {
+        let to_download = if direction.pulls() {
+            self.to_download
+        } else {
+            info!("Desktop overwrites handheld, skipping downloads");
+            Vec::new()
+        };
+        for (to_drain, operation, after) in to_download {
+            let (operation, card_no) = operation.into_card();
+            let after = operation.after_download(after);
+            // a backup never changes the handheld
+            let after = if direction == SyncDirection::Backup {
+                AfterDownload::Keep
+            } else {
+                after
+            };
+            // only resetting the sync flags writes to the database, deleting it happens once closed
+            let opened = if after == AfterDownload::KeepAndResetFlags {
+                ss.open_db(to_drain.clone(), card_no)
+            } else {
+                ss.open_db_read_only(to_drain.clone(), card_no)
+            };
+            let Ok(handle) = opened else {
                 continue;
             };
-            let results = Self::drain_db(handle, ss)?;
-            match operation {
-                ConduitDBSink::Dynamic(mut op) => op(results)?,
+            ss.closing(handle, || {
+                Self::download_into(operation, &to_drain, card_no, handle, &self.user_dir, ss)?;
+                let db_name = String::from_utf8_lossy(to_drain.as_bytes()).into_owned();
+                let log_str = match after {
+                    AfterDownload::Delete => {
+                        format!("Downloaded {db_name}, removing from handheld\n")
+                    }
+                    AfterDownload::Keep => format!("Downloaded {db_name}, keeping on handheld\n"),
+                    AfterDownload::KeepAndResetFlags => {
+                        format!(
+                            "Downloaded {db_name}, keeping on handheld and resetting sync flags\n"
+                        )
+                    }
+                };
+                ss.log_to_hs_log(CString::new(log_str).unwrap())?;
+                if after == AfterDownload::KeepAndResetFlags {
+                    Self::reset_sync_flags(handle, ss)?;
+                }
+                Ok(())
+            })?;
+            if after == AfterDownload::Delete {
+                Self::remove_db(to_drain, card_no, ss)?;
             }
-            Self::close_db(handle, &ss)?;
-            Self::remove_db(to_drain, &ss)?;
         }
 
-        for to_remove in self.to_remove {
-            Self::remove_db(to_remove, &ss)?;
-        }
-        for to_remove in self.overwrite.iter().map(|(name, _, _)| name.clone()) {
-            Self::remove_db(to_remove, &ss)?;
+        if let Some((backup_dir, card_no)) = self.backup_dir.filter(|_| direction.pulls()) {
+            Self::backup(&in_user_dir(&self.user_dir, backup_dir), card_no, ss)?;
         }
 
-        for (name, ty, db) in self.create_if_not_exists {
-            let handle = Self::create_db(name, self.creator_id, ty, false, &ss)?;
-            Self::fill_db(handle, db, &ss)?;
-            Self::close_db(handle, &ss)?;
+        if direction.pushes() {
+            if let Some((restore_dir, options)) = self.restore_from {
+                Self::restore(&in_user_dir(&self.user_dir, restore_dir), options, ss)?;
+            }
+            for (to_remove, card_no) in self.to_remove {
+                Self::remove_db(to_remove, card_no, ss)?;
+            }
+            for (to_remove, card_no) in self
+                .overwrite
+                .iter()
+                .filter(|source| !matches!(source.contents, SourceContents::Changes(_)))
+                .map(|source| (source.name.clone(), source.card_no))
+            {
+                Self::remove_db(to_remove, card_no, ss)?;
+            }
+
+            let sources = self
+                .create_if_not_exists
+                .into_iter()
+                .map(|source| (source, true))
+                .chain(self.overwrite.into_iter().map(|source| (source, false)));
+            for (source, create_only) in sources {
+                // an error writing the contents is returned once the database is closed
+                let (handle, written) = match source.contents {
+                    SourceContents::Full(db) => {
+                        let Some(handle) = Self::create_source_db(
+                            source.name,
+                            self.creator_id,
+                            source.ty,
+                            source.resource,
+                            source.card_no,
+                            create_only,
+                            ss,
+                        )?
+                        else {
+                            continue;
+                        };
+                        (handle, Self::fill_db(handle, db, source.keep_ids, ss))
+                    }
+                    SourceContents::Changes(diff) => {
+                        let changes = diff.into_changes();
+                        ss.log_to_hs_log(
+                            CString::new(format!(
+                                "Applying {} changes to {}\n",
+                                changes.len(),
+                                String::from_utf8_lossy(source.name.as_bytes())
+                            ))
+                            .unwrap(),
+                        )?;
+                        let handle = Self::open_or_create_db(
+                            source.name,
+                            self.creator_id,
+                            source.ty,
+                            source.card_no,
+                            ss,
+                        )?;
+                        (handle, Self::apply_changes(handle, changes, ss).map(|_| ()))
+                    }
+                    SourceContents::Blobs(blobs) => {
+                        let Some(handle) = Self::create_source_db(
+                            source.name,
+                            self.creator_id,
+                            source.ty,
+                            false,
+                            source.card_no,
+                            create_only,
+                            ss,
+                        )?
+                        else {
+                            continue;
+                        };
+                        (handle, Self::write_blobs(handle, blobs, ss))
+                    }
+                };
+                ss.closing(handle, || {
+                    written?;
+                    Self::write_info_blocks(handle, source.info, ss)
+                })?;
+            }
+            for (name, card_no, edits) in self.category_edits {
+                let handle = ss.open_db(name, card_no)?;
+                ss.closing(handle, || Self::edit_categories(handle, edits, ss))?;
+            }
+            for call in self.app_calls {
+                let result = ss.call_app(call.creator, call.action_code, call.params)?;
+                ss.log_to_hs_log(
+                    CString::new(format!(
+                        "Sent launch code {} to {}, result {}\n",
+                        call.action_code,
+                        String::from_utf8_lossy(&call.creator),
+                        result.result_code
+                    ))
+                    .unwrap(),
+                )?;
+            }
+        } else {
+            info!("Sync type does not allow changes to the handheld, skipping pushes");
         }
-        for (name, ty, db) in self.overwrite {
-            let handle = Self::create_db(name, self.creator_id, ty, false, &ss)?;
-            Self::fill_db(handle, db, &ss)?;
-            Self::close_db(handle, &ss)?;
+
+        if !self.to_sync.is_empty() {
+            // the dirty flags on the handheld only describe changes since it last synced, which
+            // may not have been with this PC
+            let slow = self.sync_type == eSyncTypes::eSlow || ss.last_synced_elsewhere()?;
+            let mirror_dir = self
+                .user_dir
+                .map(|dir| dir.join(String::from_utf8_lossy(self.name.as_bytes()).into_owned()));
+            if mirror_dir.is_none() {
+                info!("No user folder available, desktop mirrors will not be saved");
+            }
+            for task in self.to_sync {
+                let mirror_path = mirror_dir.as_ref().map(|dir| {
+                    dir.join(format!(
+                        "{}.mirror",
+                        String::from_utf8_lossy(task.name.as_bytes())
+                    ))
+                });
+                Self::sync_records(task, self.creator_id, direction, slow, mirror_path, ss)?;
+            }
         }
 
         Ok(())
     }
 }
 
-struct SyncSession {
+/// The find calls ignore SYNC_DB_INFO_OPT_GET_MAX_REC_SIZE, it only works with SyncReadOpenDbInfo
+const FIND_DB_OPTIONS: u8 = SYNC_DB_INFO_OPT_GET_ATTRIBUTES | SYNC_DB_INFO_OPT_GET_SIZE;
+
+/// A launch code to send to an application on the handheld
+struct AppCall {
+    creator: [c_uchar; 4],
+    action_code: u16,
+    params: Vec<u8>,
+}
+
+/// What an application or module on the handheld returned from a launch code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppCallResult {
+    /// Result code set by the application or module
+    pub result_code: u32,
+    pub reply: Vec<u8>,
+}
+
+/// Which memory of a card to look in for databases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbLocation {
+    Ram,
+    Rom,
+}
+
+/// A registered connection to the HotSync Manager, valid for the duration of the conduit's sync.
+/// `Conduit::sync` opens its own; open one directly to query the handheld before deciding what
+/// to sync, then pass it to `Conduit::sync_in`
+pub struct SyncSession {
     // cond_mgr: ConduitManager,
     api: Container<SyncMgrApi>,
     open_cond: CONDHANDLE,
+    pc_id: Option<u32>,
+    capabilities: Capabilities,
+    /// Features already read from the handheld, keyed by creator and number
+    features: RefCell<HashMap<(u32, u16), Option<u32>>>,
 }
 
 impl SyncSession {
-    fn init() -> Result<Self, ConduitError> {
+    /// Load the Sync Manager and register with HotSync. Pass the session to `Conduit::sync_in`,
+    /// or call `shutdown` when done with it
+    pub fn init() -> Result<Self, ConduitError> {
         let cond_mgr = ConduitManager::initialize()?;
-        let sync_mgr_dll_path = dbg!(cond_mgr.get_sync_mgr_dll_path()?);
+        let sync_mgr_dll_path = cond_mgr.get_sync_mgr_dll_path()?;
+        debug!("Loading Sync Manager from {}", sync_mgr_dll_path.display());
+        let pc_id = cond_mgr.get_pc_identifier().ok();
         let current_dir = std::env::current_dir().unwrap();
         std::env::set_current_dir(sync_mgr_dll_path.parent().unwrap()).unwrap();
         let api: Container<SyncMgrApi> = unsafe { Container::load(sync_mgr_dll_path) }?;
         std::env::set_current_dir(current_dir).unwrap();
-        eprintln!("Loaded api");
+        debug!("Loaded Sync Manager API");
         let mut open_cond_init = MaybeUninit::new(0);
         let open_cond;
         unsafe {
             return_iff_conduit_err!(api.SyncRegisterConduit(open_cond_init.as_mut_ptr()));
             open_cond = open_cond_init.assume_init();
         }
-        Ok(Self { api, open_cond })
+        let capabilities = Self::read_capabilities(&api);
+        Ok(Self {
+            api,
+            open_cond,
+            pc_id,
+            capabilities,
+            features: RefCell::new(HashMap::new()),
+        })
     }
-    fn shutdown(self) -> Result<(), ConduitError> {
+
+    fn read_capabilities(api: &Container<SyncMgrApi>) -> Capabilities {
+        let (mut major, mut minor) = (0_u32, 0_u32);
+        // versions before 2.0 can't report their version
+        let api_version = match unsafe {
+            api.SyncGetAPIVersion(&mut major as *mut u32, &mut minor as *mut u32)
+        } {
+            Some(SyncManagerError::SYNCERR_NONE) => (major, minor),
+            _ => (1, 0),
+        };
+        let mut os_minor = 0_u16;
+        let os_version =
+            unsafe { api.SyncGetHHOSVersion(&mut os_minor as *mut u16) }.map(|os_major| {
+                RomVersion {
+                    major: os_major as u8,
+                    minor: os_minor as u8,
+                    fix: 0,
+                    stage: 0,
+                    build: 0,
+                }
+            });
+        Capabilities {
+            api_version,
+            os_version,
+            has_find_calls: api.has_SyncReadOpenDbInfo()
+                && api.has_SyncFindDbByName()
+                && api.has_SyncFindDbByTypeCreator(),
+        }
+    }
+    pub fn shutdown(self) -> Result<(), ConduitError> {
         unsafe {
             return_iff_conduit_err!(self.api.SyncUnRegisterConduit(self.open_cond));
         }
         Ok(())
     }
-    fn log_to_hs_log(&self, line: CString) -> Result<(), ConduitError> {
+    /// List the databases on a card, paging through the handheld's database list
+    pub fn list_databases(
+        &self,
+        card_no: u8,
+        location: DbLocation,
+    ) -> Result<Vec<DatabaseInfo>, ConduitError> {
+        const PAGE_LEN: usize = 64;
+        let mut ret = Vec::new();
+        let mut start = 0;
+        loop {
+            let mut page: Vec<CDbList> = (0..PAGE_LEN).map(|_| CDbList::new()).collect();
+            let mut count = PAGE_LEN as c_int;
+            let ret_val = unsafe {
+                self.api.SyncReadDBList(
+                    card_no,
+                    start,
+                    c_int::from(location == DbLocation::Ram),
+                    page.as_mut_ptr(),
+                    &mut count as *mut c_int,
+                )
+            };
+            // the handheld reports the end of the list as not found
+            if ret_val == SyncManagerError::SYNCERR_NOT_FOUND {
+                break;
+            }
+            return_iff_conduit_err!(ret_val);
+            let page: Vec<DatabaseInfo> = page
+                .iter()
+                .take(count.clamp(0, PAGE_LEN as c_int) as usize)
+                .map(CDbList::to_info)
+                .collect();
+            let Some(last) = page.last() else {
+                break;
+            };
+            start = last.index + 1;
+            ret.extend(page);
+        }
+        Ok(ret)
+    }
+
+    /// Look up a database by name. `None` if it doesn't exist on the card
+    pub fn find_db_by_name(
+        &self,
+        card_no: u8,
+        name: &CString,
+    ) -> Result<Option<FoundDatabase>, ConduitError> {
+        self.capabilities.require(Capability::FindDatabases)?;
+        let mut params = SyncFindDbByNameParams::new(card_no as u32, name, FIND_DB_OPTIONS);
+        let mut info = MaybeUninit::<SyncDatabaseInfoType>::zeroed();
+        let ret_val = unsafe {
+            self.api
+                .SyncFindDbByName(&mut params as *mut _, info.as_mut_ptr())
+        }
+        .ok_or(self.unsupported(Capability::FindDatabases))?;
+        if ret_val == SyncManagerError::SYNCERR_NOT_FOUND {
+            return Ok(None);
+        }
+        return_iff_conduit_err!(ret_val);
+        Ok(Some(
+            unsafe { info.assume_init() }.to_found(FIND_DB_OPTIONS),
+        ))
+    }
+
+    /// Find every database with this type and creator, on any card
+    pub fn find_dbs_by_type_creator(
+        &self,
+        ty: [c_uchar; 4],
+        creator: [c_uchar; 4],
+    ) -> Result<Vec<FoundDatabase>, ConduitError> {
+        self.capabilities.require(Capability::FindDatabases)?;
+        let mut ret = Vec::new();
+        let mut search = SYNC_DB_SRCH_OPT_NEW_SEARCH;
+        loop {
+            let mut params = SyncFindDbByTypeCreatorParams::new(
+                uchars_to_u32(ty),
+                uchars_to_u32(creator),
+                FIND_DB_OPTIONS,
+                search,
+            );
+            let mut info = MaybeUninit::<SyncDatabaseInfoType>::zeroed();
+            let ret_val = unsafe {
+                self.api
+                    .SyncFindDbByTypeCreator(&mut params as *mut _, info.as_mut_ptr())
+            }
+            .ok_or(self.unsupported(Capability::FindDatabases))?;
+            // the search continues from the last match until nothing is left
+            if ret_val == SyncManagerError::SYNCERR_NOT_FOUND {
+                break;
+            }
+            return_iff_conduit_err!(ret_val);
+            ret.push(unsafe { info.assume_init() }.to_found(FIND_DB_OPTIONS));
+            search = 0;
+        }
+        Ok(ret)
+    }
+
+    /// Read the records with these unique IDs from a database on the handheld, keyed by ID,
+    /// without downloading the rest. IDs that aren't on the handheld are left out
+    pub fn read_records_by_id(
+        &self,
+        name: &CString,
+        card_no: u8,
+        ids: &[u32],
+    ) -> Result<HashMap<u32, RecordChange>, ConduitError> {
+        let handle = self.open_db_read_only(name.clone(), card_no)?;
+        self.closing(handle, || self.read_recs_by_id(ids, handle))
+    }
+
+    /// The HotSync user name and ID stored on the handheld
+    pub fn read_user_info(&self) -> Result<UserInfo, ConduitError> {
+        let mut info = MaybeUninit::new(CUserIDInfo::new());
+        unsafe {
+            return_iff_conduit_err!(self.api.SyncReadUserID(info.as_mut_ptr()));
+            Ok(info.assume_init().to_user_info())
+        }
+    }
+
+    /// The handheld's ROM version and product ID
+    pub fn read_system_info(&self) -> Result<SystemInfo, ConduitError> {
+        let mut product_id = [0_u8; CSystemInfo::SYNC_MAX_PROD_ID_SIZE];
+        let mut info = CSystemInfo::new(&mut product_id);
+        return_iff_conduit_err!(unsafe { self.api.SyncReadSystemInfo(&mut info as *mut _) });
+        Ok(info.to_system_info())
+    }
+
+    /// Every memory card on the handheld, in card number order
+    pub fn list_cards(&self) -> Result<Vec<CardInfo>, ConduitError> {
+        let mut ret = Vec::new();
+        for card_no in 0..=u8::MAX {
+            let mut info = CCardInfo::new(card_no);
+            let ret_val = unsafe { self.api.SyncReadSingleCardInfo(&mut info as *mut _) };
+            // cards are numbered from 0 without gaps
+            if ret_val == SyncManagerError::SYNCERR_NOT_FOUND {
+                break;
+            }
+            return_iff_conduit_err!(ret_val);
+            ret.push(info.to_card_info());
+        }
+        Ok(ret)
+    }
+
+    /// Sizes and free space of a memory card on the handheld
+    pub fn read_card_info(&self, card_no: u8) -> Result<CardInfo, ConduitError> {
+        let mut info = CCardInfo::new(card_no);
+        return_iff_conduit_err!(unsafe { self.api.SyncReadSingleCardInfo(&mut info as *mut _) });
+        Ok(info.to_card_info())
+    }
+
+    /// The version of the OS running on the handheld, which may be newer than the ROM. Only the
+    /// major and minor numbers are reported
+    pub fn read_os_version(&self) -> Result<RomVersion, ConduitError> {
+        self.capabilities
+            .os_version
+            .ok_or(self.unsupported(Capability::OsVersion))
+    }
+
+    /// Send a launch code and parameter block to the application with this creator ID, returning
+    /// its result code and reply. Fails with `ConduitError::RecordTooLarge` if the parameter block
+    /// is over `MAX_RECORD_SIZE`
+    pub fn call_app(
+        &self,
+        creator: [c_uchar; 4],
+        action_code: u16,
+        mut params: Vec<u8>,
+    ) -> Result<AppCallResult, ConduitError> {
+        let mut reply = vec![0_u8; u16::MAX as usize];
+        let mut out_params =
+            CCallAppParams::new_for_sending(uchars_to_u32(creator), action_code, &mut params)?;
+        let mut in_params = CCallAppParams::new_for_reply(&mut reply)?;
+        return_iff_conduit_err!(unsafe {
+            self.api
+                .SyncCallApplication(&mut out_params as *mut _, &mut in_params as *mut _)
+        });
+        let (result_code, reply_len) = (in_params.get_result_code(), in_params.get_param_size());
+        reply.truncate(reply_len as usize);
+        Ok(AppCallResult {
+            result_code: result_code as u32,
+            reply,
+        })
+    }
+
+    /// Call a module on the handheld by creator and type, returning its result code and reply
+    pub fn call_remote_module(
+        &self,
+        creator: [c_uchar; 4],
+        ty: [c_uchar; 4],
+        action_code: u16,
+        mut params: Vec<u8>,
+    ) -> Result<AppCallResult, ConduitError> {
+        self.capabilities.require(Capability::RemoteModules)?;
+        let mut reply = vec![0_u8; u16::MAX as usize];
+        let mut call = CCallModuleParams::new(
+            uchars_to_u32(creator),
+            uchars_to_u32(ty),
+            action_code,
+            &mut params,
+            &mut reply,
+        );
+        let ret_val = unsafe { self.api.SyncCallRemoteModule(&mut call as *mut _) };
+        return_iff_conduit_err!(ret_val.unwrap_or(SyncManagerError::SYNCERR_UNKNOWN_REQUEST));
+        let (result_code, reply_len) = (call.get_result_code(), call.get_result_size());
+        reply.truncate(reply_len as usize);
+        Ok(AppCallResult { result_code, reply })
+    }
+
+    /// Read a feature from the handheld, `None` if it isn't set. Each feature is only read from
+    /// the handheld once per session
+    pub fn read_feature(
+        &self,
+        creator: [c_uchar; 4],
+        num: u16,
+    ) -> Result<Option<u32>, ConduitError> {
+        self.capabilities.require(Capability::Features)?;
+        let key = (uchars_to_u32(creator), num);
+        if let Some(value) = self.features.borrow().get(&key) {
+            return Ok(*value);
+        }
+        let mut value = 0_u32;
+        let ret_val = unsafe { self.api.SyncReadFeature(key.0, num, &mut value as *mut u32) }
+            .unwrap_or(SyncManagerError::SYNCERR_UNKNOWN_REQUEST);
+        let value = if ret_val == SyncManagerError::SYNCERR_NOT_FOUND {
+            None
+        } else {
+            return_iff_conduit_err!(ret_val);
+            Some(value)
+        };
+        self.features.borrow_mut().insert(key, value);
+        Ok(value)
+    }
+
+    /// What the Sync Manager and handheld support, as read when the session started
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// The error for a call this Sync Manager doesn't provide
+    fn unsupported(&self, capability: Capability) -> ConduitError {
+        ConduitError::Unsupported(capability, self.capabilities.api_version)
+    }
+
+    fn read_rec_by_id(
+        &self,
+        id: u32,
+        size_estimate: Option<usize>,
+        handle: openDatabaseHandle,
+    ) -> Result<RecordChange, ConduitError> {
+        let record = self.read_raw_rec(
+            size_estimate,
+            |bytes| CRawRecordInfo::new_for_reading_by_id(handle, id, bytes),
+            |rec| unsafe { self.api.SyncReadRecordById(rec) },
+        )?;
+        Ok(RecordChange::from_raw(
+            record.id,
+            record.attributes,
+            record.category,
+            record.data,
+        ))
+    }
+
+    /// Read a record or resource into a buffer sized from `size_estimate`, retrying once with the
+    /// size the handheld reports if it doesn't fit. `new_info` points a request at the buffer and
+    /// `read` sends it
+    fn read_raw_rec<'buffer>(
+        &self,
+        size_estimate: Option<usize>,
+        new_info: impl Fn(&mut Vec<u8>) -> CRawRecordInfo<'buffer>,
+        read: impl Fn(*mut CRawRecordInfo<'buffer>) -> SyncManagerError,
+    ) -> Result<RawRead, ConduitError> {
+        let mut bytes = vec![0_u8; self.read_buffer_len(size_estimate)];
+
+        let mut to_fill = MaybeUninit::new(new_info(&mut bytes));
+        let mut ret_val = read(to_fill.as_mut_ptr());
+
+        // retry with the correct buffer size if too small
+        if ret_val == SyncManagerError::SYNCERR_LOCAL_BUFF_TOO_SMALL {
+            let new_size = unsafe { to_fill.assume_init_ref().get_record_size() } as usize;
+            bytes.resize(self.read_buffer_len(Some(new_size)), 0_u8);
+            to_fill = MaybeUninit::new(new_info(&mut bytes));
+            ret_val = read(to_fill.as_mut_ptr());
+        }
+        return_iff_conduit_err!(ret_val);
+
+        let record = unsafe { to_fill.assume_init() };
+        let (id, attributes, category, resource_id, size) = (
+            record.get_id(),
+            record.get_attributes(),
+            record.get_category(),
+            record.get_resource_id(),
+            record.get_record_size(),
+        );
+        bytes.truncate(size as usize);
+        Ok(RawRead {
+            id,
+            attributes,
+            category,
+            resource_id,
+            data: bytes,
+        })
+    }
+
+    /// Read the records with these unique IDs, leaving out any the handheld doesn't have
+    fn read_recs_by_id(
+        &self,
+        ids: &[u32],
+        handle: openDatabaseHandle,
+    ) -> Result<HashMap<u32, RecordChange>, ConduitError> {
+        let max_size = self.max_record_size(handle);
+        let mut ret = HashMap::with_capacity(ids.len());
+        for id in ids {
+            match self.read_rec_by_id(*id, max_size, handle) {
+                Ok(change) => {
+                    ret.insert(*id, change);
+                }
+                Err(ConduitError::Sync(SyncManagerError::SYNCERR_NOT_FOUND)) => (),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(ret)
+    }
+
+    fn open_db(&self, to_open: CString, card_no: u8) -> Result<openDatabaseHandle, ConduitError> {
+        self.open_db_in_mode(
+            to_open,
+            card_no,
+            eDbOpenModes::eDbExclusive | eDbOpenModes::eDbRead | eDbOpenModes::eDbWrite,
+        )
+    }
+
+    /// Open a database only for reading, which also works for ROM databases and ones that can't be
+    /// written to
+    fn open_db_read_only(
+        &self,
+        to_open: CString,
+        card_no: u8,
+    ) -> Result<openDatabaseHandle, ConduitError> {
+        self.open_db_in_mode(to_open, card_no, eDbOpenModes::eDbRead)
+    }
+
+    fn open_db_in_mode(
+        &self,
+        to_open: CString,
+        card_no: u8,
+        mode: eDbOpenModes,
+    ) -> Result<openDatabaseHandle, ConduitError> {
+        let mut handle = MaybeUninit::new(openDatabaseHandle::default());
+        let m_name = {
+            let mut name = [0; DB_NAMELEN];
+            for (idx, char) in to_open.into_bytes().into_iter().enumerate() {
+                if idx >= DB_NAMELEN - 1 {
+                    break;
+                }
+                name[idx] = char;
+            }
+            name
+        };
+        unsafe {
+            return_iff_conduit_err!(self.api.SyncOpenDB(
+                m_name.as_ptr(),
+                card_no as c_int,
+                handle.as_mut_ptr(),
+                mode
+            ));
+            Ok(handle.assume_init())
+        }
+    }
+
+    /// The size of the largest record or resource in an open database, when the Sync Manager can
+    /// report it, so reads can size their buffer once instead of retrying
+    fn max_record_size(&self, handle: openDatabaseHandle) -> Option<usize> {
+        if !self.capabilities.supports(Capability::FindDatabases) {
+            return None;
+        }
+        let mut params = SyncReadOpenDbInfoParams::new(handle, SYNC_DB_INFO_OPT_GET_MAX_REC_SIZE);
+        let mut info = MaybeUninit::<SyncDatabaseInfoType>::zeroed();
+        let ret_val = unsafe {
+            self.api
+                .SyncReadOpenDbInfo(&mut params as *mut _, info.as_mut_ptr())
+        };
+        if ret_val != Some(SyncManagerError::SYNCERR_NONE) {
+            return None;
+        }
+        let max_size = unsafe { info.assume_init() }
+            .to_found(SYNC_DB_INFO_OPT_GET_MAX_REC_SIZE)
+            .max_record_size?;
+        Some(max_size as usize).filter(|size| *size > 0)
+    }
+
+    /// Run `work` on an open database, then close it whether or not `work` succeeded. An error
+    /// from `work` is returned over one from closing
+    fn closing<T>(
+        &self,
+        handle: openDatabaseHandle,
+        work: impl FnOnce() -> Result<T, ConduitError>,
+    ) -> Result<T, ConduitError> {
+        let res = work();
+        let closed = self.close_db(handle);
+        let res = res?;
+        closed?;
+        Ok(res)
+    }
+
+    fn close_db(&self, handle: openDatabaseHandle) -> Result<(), ConduitError> {
+        let log_str = format!("Closing database\n");
+        self.log_to_hs_log(CString::new(log_str).unwrap())?;
+        return_iff_conduit_err!(unsafe { self.api.SyncCloseDB(handle) });
+        Ok(())
+    }
+    /// How large a buffer to read a record or block into, given its size if known. Without
+    /// partial reads the handheld can't report the real size of anything that doesn't fit, so
+    /// use the largest possible
+    fn read_buffer_len(&self, size: Option<usize>) -> usize {
+        const DEFAULT_READ_LEN: usize = 1024;
+        if !self.capabilities.supports(Capability::PartialReads) {
+            return u16::MAX as usize;
+        }
+        size.filter(|size| *size > 0)
+            .unwrap_or(DEFAULT_READ_LEN)
+            .min(u16::MAX as usize)
+    }
+
+    /// The time on the handheld clock, which keeps local time `utc_offset` seconds east of UTC.
+    /// `None` if the clock was never set
+    pub fn read_clock(&self, utc_offset: i32) -> Result<Option<SystemTime>, ConduitError> {
+        let mut secs = 0_i32;
+        return_iff_conduit_err!(unsafe { self.api.SyncReadSysDateTime(&mut secs as *mut i32) });
+        Ok(from_palm_local(secs as u32, utc_offset))
+    }
+
+    /// Set the handheld clock, which keeps local time `utc_offset` seconds east of UTC
+    pub fn write_clock(&self, time: SystemTime, utc_offset: i32) -> Result<(), ConduitError> {
+        return_iff_conduit_err!(unsafe {
+            self.api
+                .SyncWriteSysDateTime(to_palm_local(time, utc_offset) as i32)
+        });
+        Ok(())
+    }
+
+    /// Whether the handheld's last sync was with a different PC
+    fn last_synced_elsewhere(&self) -> Result<bool, ConduitError> {
+        let Some(pc_id) = self.pc_id else {
+            return Ok(false);
+        };
+        Ok(self.read_user_info()?.last_sync_pc != pc_id)
+    }
+
+    /// Add a line to the HotSync log shown to the user
+    pub fn log_to_hs_log(&self, line: CString) -> Result<(), ConduitError> {
         if let Ok(string) = line.clone().into_string() {
             log::info!("HS Log entry: {}", string);
         }
@@ -567,11 +2364,26 @@ mod test {
         assert_eq!(u32::MAX, uchars_to_u32([255_u8; 4]));
     }
 
+    #[test]
+    fn test_sync_direction() {
+        for sync_type in [
+            eSyncTypes::eDoNothing,
+            eSyncTypes::eInstall,
+            eSyncTypes::eProfileInstall,
+        ] {
+            assert!(SyncDirection::from_sync_type(sync_type).is_none());
+        }
+        let backup = SyncDirection::from_sync_type(eSyncTypes::eBackup).unwrap();
+        assert!(backup.pulls() && !backup.pushes());
+        let both = SyncDirection::from_sync_type(eSyncTypes::eSlow).unwrap();
+        assert!(both.pulls() && both.pushes());
+    }
+
     #[test]
     #[ignore]
     fn test_load_api() {
         let cond_mgr = ConduitManager::initialize().unwrap();
-        let sync_mgr_dll_path = dbg!(cond_mgr.get_sync_mgr_dll_path().unwrap());
+        let sync_mgr_dll_path = cond_mgr.get_sync_mgr_dll_path().unwrap();
         std::env::set_current_dir(sync_mgr_dll_path.parent().unwrap()).unwrap();
         let _: Container<SyncMgrApi> = unsafe { Container::load(sync_mgr_dll_path) }.unwrap();
     }
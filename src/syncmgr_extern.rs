@@ -3,7 +3,7 @@
 #![allow(unused)]
 
 use std::{
-    ffi::{c_int, c_long, c_short, c_void, CString, OsString},
+    ffi::{c_char, c_int, c_long, c_short, c_void, CStr, CString, OsString},
     marker::PhantomData,
     path::{Path, PathBuf},
 };
@@ -11,10 +11,18 @@ use std::{
 use dlopen2::wrapper::{Container, WrapperApi};
 use palmrs::database::record::pdb_record::RecordAttributes;
 
-use crate::error::SyncManagerError;
+use crate::{
+    database_info::{DatabaseInfo, FoundDatabase},
+    device_info::{CardInfo, RomVersion, SystemInfo, UserInfo},
+    error::{ConduitError, SyncManagerError},
+    palm_time::from_palm_seconds,
+};
 
 const SYNC_DB_NAMELEN: usize = 32;
 pub const DB_NAMELEN: usize = 32;
+/// Largest record, resource, or info block the Sync Manager can write (~63.8 KB). Sizes are
+/// passed as a WORD, and the Desktop Link protocol needs some of that for its own headers
+pub const MAX_RECORD_SIZE: usize = 65_331;
 const BIG_PATH: usize = 256;
 const SYNC_REMOTE_USERNAME_BUF_SIZE: usize = 64;
 const SYNC_REMOTE_PASSWORD_BUF_SIZE: usize = 64;
@@ -25,6 +33,7 @@ type byteCardNo = u8;
 type intCardNo = c_int;
 
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum eSyncTypes {
     eFast,
     eSlow,
@@ -37,6 +46,7 @@ pub enum eSyncTypes {
     eSyncTypeDoNotUse = 0xffffffff,
 }
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum eFirstSync {
     eNeither,
     ePC,
@@ -44,6 +54,7 @@ pub enum eFirstSync {
     eFirstSyncDoNotUse = 0xffffffff,
 }
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum eConnType {
     eCable,
     eModemConnType,
@@ -93,37 +104,173 @@ pub struct CSyncProperties {
     m_PathName: [core::ffi::c_uchar; BIG_PATH],  // Path to prepend for disk file names
     m_LocalName: [core::ffi::c_uchar; BIG_PATH], // Actual local disk file names
     m_UserName: [core::ffi::c_uchar; BIG_PATH],
-    m_RemoteName: [core::ffi::c_uchar; SYNC_DB_NAMELEN], // Names of remote database file names
-    m_RemoteDbList: *const c_void,                       // CDbLists of remote databases
-    m_nRemoteCount: c_int,                               // number of remote database files
-    m_Creator: u32,                                      // needed to create remote Db's
-    m_CardNo: u16,                                       // needed to create remote Db's
-    m_DbType: u32,                                       // needed to create remote Db's
-    m_AppInfoSize: u32,                                  // convenience
-    m_SortInfoSize: u32,                                 // convenience
-    m_FirstDevice: eFirstSync,                           // First time sync for 1 device
-    m_Connection: eConnType,                             // Transfer medium
-    m_Registry: [core::ffi::c_uchar; BIG_PATH],          // Full registry path for the conduit
-    m_hKey: *const c_void,                               // primary registry key
-    m_dwReserved: u32,                                   // Reserved - set to NULL
+    m_RemoteName: [*const c_char; SYNC_DB_NAMELEN], // Names of remote database file names
+    m_RemoteDbList: *const *const CDbList,          // CDbLists of remote databases
+    m_nRemoteCount: c_int,                          // number of remote database files
+    m_Creator: u32,                                 // needed to create remote Db's
+    m_CardNo: u16,                                  // needed to create remote Db's
+    m_DbType: u32,                                  // needed to create remote Db's
+    m_AppInfoSize: u32,                             // convenience
+    m_SortInfoSize: u32,                            // convenience
+    m_FirstDevice: eFirstSync,                      // First time sync for 1 device
+    m_Connection: eConnType,                        // Transfer medium
+    m_Registry: [core::ffi::c_uchar; BIG_PATH],     // Full registry path for the conduit
+    m_hKey: *const c_void,                          // primary registry key
+    m_dwReserved: u32,                              // Reserved - set to NULL
+}
+
+impl eSyncTypes {
+    fn from_raw(raw: u32) -> Self {
+        match raw {
+            0 => Self::eFast,
+            1 => Self::eSlow,
+            2 => Self::eHHtoPC,
+            3 => Self::ePCtoHH,
+            4 => Self::eInstall,
+            5 => Self::eBackup,
+            6 => Self::eDoNothing,
+            7 => Self::eProfileInstall,
+            _ => Self::eSyncTypeDoNotUse,
+        }
+    }
+}
+
+impl eFirstSync {
+    fn from_raw(raw: u32) -> Self {
+        match raw {
+            0 => Self::eNeither,
+            1 => Self::ePC,
+            2 => Self::eHH,
+            _ => Self::eFirstSyncDoNotUse,
+        }
+    }
+}
+
+impl eConnType {
+    fn from_raw(raw: u32) -> Self {
+        match raw {
+            0 => Self::eCable,
+            1 => Self::eModemConnType,
+            _ => Self::eConnTypeDoNotUse,
+        }
+    }
+}
+
+/// Read a null-terminated string out of a fixed size buffer, if it isn't empty
+fn buffer_to_string(buffer: &[core::ffi::c_uchar]) -> Option<String> {
+    if buffer[0] == core::ffi::c_uchar::default() {
+        return None;
+    }
+    let byte_vec = buffer
+        .iter()
+        .copied()
+        .take_while(|x| *x != core::ffi::c_uchar::default())
+        .collect::<Vec<_>>();
+    Some(String::from_utf8_lossy(&byte_vec).into_owned())
 }
 
 impl CSyncProperties {
+    /// The kind of sync HotSync has requested from this conduit
+    pub fn get_sync_type(&self) -> eSyncTypes {
+        // HotSync owns this value, so don't trust it to be a valid discriminant
+        let raw = unsafe {
+            std::ptr::addr_of!(self.m_SyncType)
+                .cast::<u32>()
+                .read_unaligned()
+        };
+        eSyncTypes::from_raw(raw)
+    }
+
     pub fn get_dir_path(&self) -> Option<PathBuf> {
-        if self.m_PathName[0] == core::ffi::c_uchar::default() {
-            return None;
-        }
-        let byte_vec = self
-            .m_PathName
-            .iter()
-            .copied()
-            .take_while(|x| *x != core::ffi::c_uchar::default())
-            .collect::<Vec<_>>();
-        let stir = String::from_utf8_lossy(&byte_vec).into_owned();
+        let stir = buffer_to_string(&self.m_PathName)?;
         let os_str: OsString = stir.try_into().unwrap();
         let path = PathBuf::from(&os_str);
         Some(path)
     }
+
+    /// Name of the local file the conduit should sync with
+    pub fn get_local_name(&self) -> Option<String> {
+        buffer_to_string(&self.m_LocalName)
+    }
+
+    /// Name of the HotSync user being synced
+    pub fn get_user_name(&self) -> Option<String> {
+        buffer_to_string(&self.m_UserName)
+    }
+
+    fn get_remote_count(&self) -> usize {
+        (self.m_nRemoteCount.max(0) as usize).min(SYNC_DB_NAMELEN)
+    }
+
+    /// Names of the handheld databases this conduit is responsible for
+    pub fn get_remote_names(&self) -> Vec<CString> {
+        let names = self.m_RemoteName;
+        names[..self.get_remote_count()]
+            .iter()
+            .filter(|name| !name.is_null())
+            .map(|name| unsafe { CStr::from_ptr(*name) }.to_owned())
+            .collect()
+    }
+
+    /// Descriptions of the handheld databases this conduit is responsible for
+    pub fn get_remote_db_list(&self) -> Vec<DatabaseInfo> {
+        let list = self.m_RemoteDbList;
+        if list.is_null() {
+            return Vec::new();
+        }
+        (0..self.get_remote_count())
+            .map(|idx| unsafe { list.add(idx).read_unaligned() })
+            .filter(|entry| !entry.is_null())
+            .map(|entry| unsafe { entry.read_unaligned() }.to_info())
+            .collect()
+    }
+
+    /// Creator ID of the conduit
+    pub fn get_creator(&self) -> [u8; 4] {
+        self.m_Creator.to_be_bytes()
+    }
+
+    /// Card number for databases created by the conduit
+    pub fn get_card_no(&self) -> u16 {
+        self.m_CardNo
+    }
+
+    /// Type code for databases created by the conduit
+    pub fn get_db_type(&self) -> [u8; 4] {
+        self.m_DbType.to_be_bytes()
+    }
+
+    pub fn get_app_info_size(&self) -> u32 {
+        self.m_AppInfoSize
+    }
+
+    pub fn get_sort_info_size(&self) -> u32 {
+        self.m_SortInfoSize
+    }
+
+    /// Whether this is the first time the handheld or the PC have been synced
+    pub fn get_first_sync(&self) -> eFirstSync {
+        let raw = unsafe {
+            std::ptr::addr_of!(self.m_FirstDevice)
+                .cast::<u32>()
+                .read_unaligned()
+        };
+        eFirstSync::from_raw(raw)
+    }
+
+    pub fn get_connection_type(&self) -> eConnType {
+        let raw = unsafe {
+            std::ptr::addr_of!(self.m_Connection)
+                .cast::<u32>()
+                .read_unaligned()
+        };
+        eConnType::from_raw(raw)
+    }
+
+    /// Full registry path for the conduit's settings
+    pub fn get_registry_path(&self) -> Option<String> {
+        buffer_to_string(&self.m_Registry)
+    }
 }
 
 bitflags::bitflags! {
@@ -220,20 +367,42 @@ impl CDbCreateDB {
         }
     }
 
+    /// Create the database with these header flags. Only the flags the handheld accepts on
+    /// creation are kept, and the resource flag is left as given to `new`
+    pub(crate) fn with_flags(mut self, flags: eDbFlags) -> Self {
+        let settable = eDbFlags::eBackupDb
+            | eDbFlags::eOkToInstallNewer
+            | eDbFlags::eResetAfterInstall
+            | eDbFlags::eCopyPRevention;
+        self.m_Flags |= (flags & settable).bits();
+        self
+    }
+
+    pub(crate) fn with_version(mut self, version: u16) -> Self {
+        self.m_Version = version;
+        self
+    }
+
+    /// Create the database on this memory card instead of card 0
+    pub(crate) fn with_card(mut self, card_no: u8) -> Self {
+        self.m_CardNo = card_no;
+        self
+    }
+
     pub fn handle(self) -> openDatabaseHandle {
         self.m_FileHandle
     }
 }
 
 #[repr(packed, C)]
-pub struct CDbGenInfo {
+pub struct CDbGenInfo<'buffer> {
     /// Name of remote database file
     m_FileName: [core::ffi::c_uchar; SYNC_DB_NAMELEN],
     /// When reading, the caller must fill this in
     /// with the size of the buffer pointed to by m_pBytes;
     /// When writing, the caller must set both this field
     /// and m_BytesRead to the size of the block being written.
-    m_TotalBytes: i16,
+    m_TotalBytes: u16,
     /// *This field is poorly named*
     /// When reading, it will
     /// be filled in with the actual size of
@@ -246,11 +415,43 @@ pub struct CDbGenInfo {
     /// be copied by Sync API before v2.1).
     /// When writing, the caller must set this field (in addition to
     /// m_TotalBytes) to the size of the block being written.
-    m_BytesRead: i16,
+    m_BytesRead: u16,
     // pointer to caller's buffer
     m_pBytes: *mut u8,
     // Reserved - set to NULL
-    m_dwReserved: i32,
+    m_dwReserved: u32,
+    /// zst to track lifetime of data buffer
+    buffer_lifetime: PhantomData<&'buffer [u8]>,
+}
+
+impl<'buffer> CDbGenInfo<'buffer> {
+    pub(crate) fn new_for_reading(bytes: &mut Vec<u8>) -> Self {
+        Self {
+            m_FileName: [0; SYNC_DB_NAMELEN],
+            m_TotalBytes: bytes.len() as u16,
+            m_BytesRead: 0,
+            m_pBytes: bytes.as_mut_ptr(),
+            m_dwReserved: 0,
+            buffer_lifetime: PhantomData,
+        }
+    }
+
+    pub(crate) fn new_for_writing(bytes: &mut Vec<u8>) -> Result<Self, ConduitError> {
+        let len = checked_write_len(bytes)?;
+        Ok(Self {
+            m_FileName: [0; SYNC_DB_NAMELEN],
+            m_TotalBytes: len,
+            m_BytesRead: len,
+            m_pBytes: bytes.as_mut_ptr(),
+            m_dwReserved: 0,
+            buffer_lifetime: PhantomData,
+        })
+    }
+
+    /// The full size of the block on the handheld, which may be larger than the buffer
+    pub(crate) fn get_block_size(&self) -> u16 {
+        self.m_BytesRead
+    }
 }
 
 ///  Used by all the Record Oriented API's. Houses the DT_Link version
@@ -304,20 +505,21 @@ impl<'buffer> CRawRecordInfo<'buffer> {
         m_CatId: i16,
         resource_type_and_id: Option<(u32, u16)>,
         bytes: &mut Vec<u8>,
-    ) -> Self {
-        Self {
+    ) -> Result<Self, ConduitError> {
+        let len = checked_write_len(bytes)?;
+        Ok(Self {
             m_FileHandle,
             m_RecId: resource_type_and_id.map(|(ty, _)| ty).unwrap_or(0),
             m_RecIndex: resource_type_and_id.map(|(_, id)| id).unwrap_or(0),
             m_Attribs,
             m_CatId,
             m_ConduitId: 0,
-            m_RecSize: bytes.len() as u32,
-            m_TotalBytes: bytes.len() as u16,
+            m_RecSize: len as u32,
+            m_TotalBytes: len,
             m_pBytes: bytes.as_mut_ptr(),
             m_dwReserved: 0,
             buffer_lifetime: PhantomData,
-        }
+        })
     }
 
     pub(crate) fn new_for_reading_by_index(
@@ -340,17 +542,77 @@ impl<'buffer> CRawRecordInfo<'buffer> {
         }
     }
 
-    pub(crate) fn get_size(&self) -> u16 {
-        self.m_TotalBytes
+    pub(crate) fn new_for_reading_by_id(
+        m_FileHandle: openDatabaseHandle,
+        id: u32,
+        bytes: &mut Vec<u8>,
+    ) -> Self {
+        Self {
+            m_FileHandle,
+            m_RecId: id,
+            m_RecIndex: 0,
+            m_Attribs: 0,
+            m_CatId: 0,
+            m_ConduitId: 0,
+            m_RecSize: bytes.len() as u32,
+            m_TotalBytes: bytes.len() as u16,
+            m_pBytes: bytes.as_mut_ptr(),
+            m_dwReserved: 0,
+            buffer_lifetime: PhantomData,
+        }
+    }
+
+    pub(crate) fn new_for_deleting(m_FileHandle: openDatabaseHandle, id: u32) -> Self {
+        Self {
+            m_FileHandle,
+            m_RecId: id,
+            m_RecIndex: 0,
+            m_Attribs: 0,
+            m_CatId: 0,
+            m_ConduitId: 0,
+            m_RecSize: 0,
+            m_TotalBytes: 0,
+            m_pBytes: std::ptr::null_mut(),
+            m_dwReserved: 0,
+            buffer_lifetime: PhantomData,
+        }
+    }
+
+    /// Set the unique ID of the record being written. 0 lets the handheld assign one
+    pub(crate) fn with_record_id(mut self, id: u32) -> Self {
+        self.m_RecId = id;
+        self
     }
 
     pub(crate) fn get_attributes(&self) -> u8 {
         self.m_Attribs
     }
 
+    pub(crate) fn get_category(&self) -> i16 {
+        self.m_CatId
+    }
+
+    /// The full size of the record on the handheld, which may be larger than the buffer
+    pub(crate) fn get_record_size(&self) -> u32 {
+        self.m_RecSize
+    }
+
     pub(crate) fn get_id(&self) -> u32 {
         self.m_RecId
     }
+
+    /// The resource ID, when reading a resource
+    pub(crate) fn get_resource_id(&self) -> u16 {
+        self.m_RecIndex
+    }
+}
+
+/// The size to write for a buffer, refusing anything the Sync Manager would truncate
+fn checked_write_len(bytes: &[u8]) -> Result<u16, ConduitError> {
+    if bytes.len() > MAX_RECORD_SIZE {
+        return Err(ConduitError::RecordTooLarge(bytes.len()));
+    }
+    Ok(bytes.len() as u16)
 }
 
 #[derive(Debug)]
@@ -399,9 +661,9 @@ impl<'a> CRawPreferenceInfo<'a> {
 #[repr(packed, C)]
 pub struct CUserIDInfo {
     m_pName: [core::ffi::c_uchar; SYNC_REMOTE_USERNAME_BUF_SIZE],
-    m_NameLength: i16,
+    m_NameLength: c_int,
     m_Password: [core::ffi::c_uchar; SYNC_REMOTE_PASSWORD_BUF_SIZE],
-    m_PasswdLength: i16,
+    m_PasswdLength: c_int,
     /// Date/Time of last synchronization
     m_LastSyncDate: i32,
     m_LastSyncPC: u32,
@@ -411,10 +673,48 @@ pub struct CUserIDInfo {
     m_dwReserved: u32,
 }
 
+impl CUserIDInfo {
+    pub(crate) fn new() -> Self {
+        Self {
+            m_pName: [0; SYNC_REMOTE_USERNAME_BUF_SIZE],
+            m_NameLength: 0,
+            m_Password: [0; SYNC_REMOTE_PASSWORD_BUF_SIZE],
+            m_PasswdLength: 0,
+            m_LastSyncDate: 0,
+            m_LastSyncPC: 0,
+            m_Id: 0,
+            m_ViewerId: 0,
+            m_dwReserved: 0,
+        }
+    }
+
+    pub(crate) fn last_sync_pc(&self) -> u32 {
+        self.m_LastSyncPC
+    }
+
+    pub(crate) fn to_user_info(&self) -> UserInfo {
+        let m_pName = self.m_pName;
+        UserInfo {
+            name: c_string_from(&m_pName, self.m_NameLength as usize),
+            user_id: self.m_Id,
+            viewer_id: self.m_ViewerId,
+            last_sync_pc: self.m_LastSyncPC,
+            last_sync_date: from_palm_seconds(self.m_LastSyncDate as u32),
+        }
+    }
+}
+
+/// Copy a string out of a fixed-size buffer, stopping at the reported length or the first nul
+fn c_string_from(buf: &[u8], len: usize) -> CString {
+    let buf = &buf[..len.min(buf.len())];
+    let len = buf.iter().position(|c| *c == 0).unwrap_or(buf.len());
+    CString::new(&buf[..len]).unwrap()
+}
+
 ///  A single element for a ReadDBList function call.
 #[repr(packed, C)]
 pub struct CDbList {
-    m_CardNum: i16,
+    m_CardNum: c_int,
     /// contains Res/Record/Backup/ReadOnly (see enum eDbFlags)
     m_DbFlags: u16,
     m_DbType: u32,
@@ -435,20 +735,191 @@ pub struct CDbList {
     m_dwReserved: i32,
 }
 
+impl CDbList {
+    pub(crate) fn new() -> Self {
+        Self {
+            m_CardNum: 0,
+            m_DbFlags: 0,
+            m_DbType: 0,
+            m_Name: [0; SYNC_DB_NAMELEN],
+            m_Creator: 0,
+            m_Version: 0,
+            m_ModNumber: 0,
+            m_Index: 0,
+            m_CreateDate: 0,
+            m_ModDate: 0,
+            m_BackupDate: 0,
+            m_miscFlags: 0,
+            m_RecCount: 0,
+            m_dwReserved: 0,
+        }
+    }
+
+    pub(crate) fn to_info(&self) -> DatabaseInfo {
+        let m_Name = self.m_Name;
+        let name_len = m_Name.iter().position(|c| *c == 0).unwrap_or(m_Name.len());
+        DatabaseInfo {
+            name: CString::new(&m_Name[..name_len]).unwrap(),
+            card_no: self.m_CardNum as u16,
+            flags: eDbFlags::from_bits_retain(self.m_DbFlags as u32),
+            misc_flags: self.m_miscFlags as u32,
+            db_type: self.m_DbType.to_be_bytes(),
+            creator: self.m_Creator.to_be_bytes(),
+            version: self.m_Version,
+            mod_number: self.m_ModNumber,
+            index: self.m_Index,
+            create_date: from_palm_seconds(self.m_CreateDate as u32),
+            mod_date: from_palm_seconds(self.m_ModDate as u32),
+            backup_date: from_palm_seconds(self.m_BackupDate as u32),
+        }
+    }
+}
+
+/// Options for the database info calls
+pub(crate) const SYNC_DB_INFO_OPT_GET_ATTRIBUTES: u8 = 0x80;
+pub(crate) const SYNC_DB_INFO_OPT_GET_SIZE: u8 = 0x40;
+pub(crate) const SYNC_DB_INFO_OPT_GET_MAX_REC_SIZE: u8 = 0x20;
+/// Search options for SyncFindDbByTypeCreator
+pub(crate) const SYNC_DB_SRCH_OPT_NEW_SEARCH: u8 = 0x80;
+pub(crate) const SYNC_DB_SRCH_OPT_ONLY_LATEST: u8 = 0x40;
+
+/// Used by SyncFindDbByName, SyncFindDbByTypeCreator, and SyncReadOpenDbInfo
+#[repr(packed, C)]
+pub struct SyncDatabaseInfoType {
+    /// Filled in if SYNC_DB_INFO_OPT_GET_ATTRIBUTES was requested
+    baseInfo: CDbList,
+    /// Filled in if SYNC_DB_INFO_OPT_GET_SIZE was requested
+    dwNumRecords: u32,
+    dwTotalBytes: u32,
+    dwDataBytes: u32,
+    dwAppBlkSize: u32,
+    dwSortBlkSize: u32,
+    /// Filled in if SYNC_DB_INFO_OPT_GET_MAX_REC_SIZE was requested
+    dwMaxRecSize: u32,
+    /// Reserved - set to NULL
+    dwReserved: u32,
+    /// LocalID of the database (internal use)
+    dwLocalID: u32,
+    /// Open ref of the database if the Sync Manager has it open, zero otherwise (internal use)
+    dwOpenRef: u32,
+}
+
+impl SyncDatabaseInfoType {
+    pub(crate) fn to_info(&self) -> DatabaseInfo {
+        // the struct is packed, so copy the field out before borrowing it
+        let base = unsafe { std::ptr::read_unaligned(std::ptr::addr_of!(self.baseInfo)) };
+        base.to_info()
+    }
+
+    /// `options` are the SYNC_DB_INFO_OPT flags the info was requested with
+    pub(crate) fn to_found(&self, options: u8) -> FoundDatabase {
+        let info = self.to_info();
+        FoundDatabase {
+            card_no: info.card_no as u32,
+            info,
+            local_id: self.dwLocalID,
+            record_count: self.dwNumRecords,
+            total_bytes: self.dwTotalBytes,
+            max_record_size: Some(self.dwMaxRecSize)
+                .filter(|_| options & SYNC_DB_INFO_OPT_GET_MAX_REC_SIZE != 0),
+        }
+    }
+}
+
+///  Used by the 'SyncFindDbByName()' API
+#[repr(packed, C)]
+pub struct SyncFindDbByNameParams {
+    bOptFlags: u8,
+    dwCardNum: u32,
+    pcDatabaseName: *const c_char,
+}
+
+impl SyncFindDbByNameParams {
+    /// `name` must outlive the call
+    pub(crate) fn new(card_no: u32, name: &CStr, options: u8) -> Self {
+        Self {
+            bOptFlags: options,
+            dwCardNum: card_no,
+            pcDatabaseName: name.as_ptr(),
+        }
+    }
+}
+
+///  Used by the 'SyncFindDbByTypeCreator()' API
+#[repr(packed, C)]
+pub struct SyncFindDbByTypeCreatorParams {
+    bOptFlags: u8,
+    bSrchFlags: u8,
+    dwType: u32,
+    dwCreator: u32,
+}
+
+impl SyncFindDbByTypeCreatorParams {
+    pub(crate) fn new(ty: u32, creator: u32, options: u8, search: u8) -> Self {
+        Self {
+            bOptFlags: options,
+            bSrchFlags: search,
+            dwType: ty,
+            dwCreator: creator,
+        }
+    }
+}
+
+///  Used by the 'SyncReadOpenDbInfo()' API
+#[repr(packed, C)]
+pub struct SyncReadOpenDbInfoParams {
+    bOptFlags: u8,
+    bDbHandle: openDatabaseHandle,
+}
+
+impl SyncReadOpenDbInfoParams {
+    pub(crate) fn new(handle: openDatabaseHandle, options: u8) -> Self {
+        Self {
+            bOptFlags: options,
+            bDbHandle: handle,
+        }
+    }
+}
+
 /// Used to obtain remote system information.
 #[repr(packed, C)]
-pub struct CSystemInfo {
+pub struct CSystemInfo<'buffer> {
     m_RomSoftVersion: u32,                    // Upon return is filled in
     m_LocalId: u32,                           // Upon return is filled in
     m_ProdIdLength: u8,                       // Upon return is filled in (actual len)
     m_AllocedLen: u8, // Supplied by caller: size of buffer for ProductIdText
     m_ProductIdText: *mut core::ffi::c_uchar, // Allocated by caller: bufer for ProductIdText
     m_dwReserved: u32, // Reserved - set to NULL
+    /// zst to track lifetime of product ID buffer
+    buffer_lifetime: PhantomData<&'buffer [u8]>,
 }
 
-impl CSystemInfo {
+impl<'buffer> CSystemInfo<'buffer> {
     /// Product ID buffer size in number of byts
     pub const SYNC_MAX_PROD_ID_SIZE: usize = 255;
+
+    pub(crate) fn new(product_id: &'buffer mut [u8; CSystemInfo::SYNC_MAX_PROD_ID_SIZE]) -> Self {
+        Self {
+            m_RomSoftVersion: 0,
+            m_LocalId: 0,
+            m_ProdIdLength: 0,
+            m_AllocedLen: Self::SYNC_MAX_PROD_ID_SIZE as u8,
+            m_ProductIdText: product_id.as_mut_ptr(),
+            m_dwReserved: 0,
+            buffer_lifetime: PhantomData,
+        }
+    }
+
+    pub(crate) fn to_system_info(&self) -> SystemInfo {
+        let len = self.m_ProdIdLength.min(self.m_AllocedLen) as usize;
+        // the buffer is borrowed for 'buffer, so it is still alive
+        let product_id = unsafe { std::slice::from_raw_parts(self.m_ProductIdText, len) };
+        SystemInfo {
+            rom_version: RomVersion::from_raw(self.m_RomSoftVersion),
+            local_id: self.m_LocalId,
+            product_id: product_id.to_vec(),
+        }
+    }
 }
 
 ///  A structure element for the SyncReadSingleCardInfo() function call.
@@ -475,16 +946,149 @@ pub struct CCardInfo {
 impl CCardInfo {
     pub const SYNC_REMOTE_CARDNAME_BUF_SIZE: usize = 32;
     pub const SYNC_REMOTE_MANUFNAME_BUF_SIZE: usize = 32;
+
+    pub(crate) fn new(card_no: u8) -> Self {
+        Self {
+            m_CardNo: card_no,
+            m_CardVersion: 0,
+            m_CreateDate: 0,
+            m_RomSize: 0,
+            m_RamSize: 0,
+            m_FreeRam: 0,
+            m_CardNameLen: 0,
+            m_ManufNameLen: 0,
+            m_CardName: [0; Self::SYNC_REMOTE_CARDNAME_BUF_SIZE],
+            m_ManufName: [0; Self::SYNC_REMOTE_MANUFNAME_BUF_SIZE],
+            m_romDbCount: 0,
+            m_ramDbCount: 0,
+            m_dwReserved: 0,
+        }
+    }
+
+    pub(crate) fn to_card_info(&self) -> CardInfo {
+        let (m_CardName, m_ManufName) = (self.m_CardName, self.m_ManufName);
+        CardInfo {
+            card_no: self.m_CardNo,
+            version: self.m_CardVersion,
+            create_date: from_palm_seconds(self.m_CreateDate as u32),
+            rom_size: self.m_RomSize,
+            ram_size: self.m_RamSize,
+            free_ram: self.m_FreeRam,
+            name: c_string_from(&m_CardName, self.m_CardNameLen as usize),
+            manufacturer: c_string_from(&m_ManufName, self.m_ManufNameLen as usize),
+            rom_db_count: self.m_romDbCount,
+            ram_db_count: self.m_ramDbCount,
+        }
+    }
 }
 
 ///  Used by the 'SyncCallApplication()' API
 #[repr(packed, C)]
-pub struct CCallAppParams {
+pub struct CCallAppParams<'buffer> {
     m_CreatorID: u32,
     m_ActionCode: u16,
     m_ResultCode: u16,
     m_ParamSize: u16,
     m_pParams: *mut u8,
+    /// zst to track lifetime of parameter buffer
+    buffer_lifetime: PhantomData<&'buffer [u8]>,
+}
+
+impl<'buffer> CCallAppParams<'buffer> {
+    /// The launch code and parameter block sent to the application
+    pub(crate) fn new_for_sending(
+        creator: u32,
+        action_code: u16,
+        params: &'buffer mut Vec<u8>,
+    ) -> Result<Self, ConduitError> {
+        Ok(Self {
+            m_CreatorID: creator,
+            m_ActionCode: action_code,
+            m_ResultCode: 0,
+            m_ParamSize: checked_write_len(params)?,
+            m_pParams: params.as_mut_ptr(),
+            buffer_lifetime: PhantomData,
+        })
+    }
+
+    /// Space for the application's reply, up to the length of `reply`
+    pub(crate) fn new_for_reply(reply: &'buffer mut Vec<u8>) -> Result<Self, ConduitError> {
+        Ok(Self {
+            m_CreatorID: 0,
+            m_ActionCode: 0,
+            m_ResultCode: 0,
+            m_ParamSize: u16::try_from(reply.len())
+                .map_err(|_| ConduitError::RecordTooLarge(reply.len()))?,
+            m_pParams: reply.as_mut_ptr(),
+            buffer_lifetime: PhantomData,
+        })
+    }
+
+    pub(crate) fn get_result_code(&self) -> u16 {
+        self.m_ResultCode
+    }
+
+    /// Size of the reply written to the buffer
+    pub(crate) fn get_param_size(&self) -> u16 {
+        self.m_ParamSize
+    }
+}
+
+///  Used by the 'SyncCallRemoteModule()' API
+#[repr(packed, C)]
+pub struct CCallModuleParams<'buffer> {
+    /// target application creator ID
+    m_dwCreatorID: u32,
+    /// target application type ID
+    m_dwTypeID: u32,
+    /// app-specific action code
+    m_wActionCode: u16,
+    m_dwParamSize: u32,
+    m_pParam: *mut c_void,
+    m_dwResultBufSize: u32,
+    m_pResultBuf: *mut c_void,
+    /// result code returned by remote module
+    m_dwResultCode: u32,
+    /// the actual result data size returned by remote module; may be greater than
+    /// m_dwResultBufSize, in which case only m_dwResultBufSize bytes were copied
+    m_dwActResultSize: u32,
+    /// Reserved - set to NULL
+    m_dwReserved: u32,
+    /// zst to track lifetime of parameter and result buffers
+    buffer_lifetime: PhantomData<&'buffer [u8]>,
+}
+
+impl<'buffer> CCallModuleParams<'buffer> {
+    pub(crate) fn new(
+        creator: u32,
+        ty: u32,
+        action_code: u16,
+        params: &'buffer mut Vec<u8>,
+        result: &'buffer mut Vec<u8>,
+    ) -> Self {
+        Self {
+            m_dwCreatorID: creator,
+            m_dwTypeID: ty,
+            m_wActionCode: action_code,
+            m_dwParamSize: params.len() as u32,
+            m_pParam: params.as_mut_ptr() as *mut c_void,
+            m_dwResultBufSize: result.len() as u32,
+            m_pResultBuf: result.as_mut_ptr() as *mut c_void,
+            m_dwResultCode: 0,
+            m_dwActResultSize: 0,
+            m_dwReserved: 0,
+            buffer_lifetime: PhantomData,
+        }
+    }
+
+    pub(crate) fn get_result_code(&self) -> u32 {
+        self.m_dwResultCode
+    }
+
+    /// Size of the result copied to the buffer
+    pub(crate) fn get_result_size(&self) -> u32 {
+        self.m_dwActResultSize.min(self.m_dwResultBufSize)
+    }
 }
 
 ///  Used by ReadPositionXMap
@@ -515,22 +1119,23 @@ pub struct SyncMgrApi {
     SyncDeleteDB:               unsafe extern "C" fn(pName: *const core::ffi::c_uchar, nCardNum: intCardNo) -> SyncManagerError,
     SyncCreateDB:               unsafe extern "C" fn(rDbStats: *mut CDbCreateDB) -> SyncManagerError,
     SyncCloseDB:                unsafe extern "C" fn(fHandle: openDatabaseHandle) -> SyncManagerError,
-    SyncGetDBRecordCount:       unsafe extern "C" fn(fHandle: openDatabaseHandle, rCount: *mut u32) -> SyncManagerError,
+    SyncGetDBRecordCount:       unsafe extern "C" fn(fHandle: openDatabaseHandle, rCount: *mut u16) -> SyncManagerError,
     SyncPurgeDeletedRecs:       unsafe extern "C" fn(fHandle: openDatabaseHandle) -> SyncManagerError,
     SyncPurgeAllRecs:           unsafe extern "C" fn(fHandle: openDatabaseHandle) -> SyncManagerError,
+    SyncPurgeAllRecsInCategory: unsafe extern "C" fn(fHandle: openDatabaseHandle, category: c_short) -> SyncManagerError,
     SyncResetSyncFlags:         unsafe extern "C" fn(fHandle: openDatabaseHandle) -> SyncManagerError,
-    SyncReadDBList:             unsafe extern "C" fn(cardNo: byteCardNo, startIX: u16, bRam: bool, pList: *mut CDbList, rCnt: *mut i16) -> SyncManagerError,
+    SyncReadDBList:             unsafe extern "C" fn(cardNo: byteCardNo, startIX: u16, bRam: c_int, pList: *mut CDbList, rCnt: *mut c_int) -> SyncManagerError,
     SyncWriteRec:               unsafe extern "C" fn(rRec: *mut CRawRecordInfo) -> SyncManagerError,
     SyncDeleteRec:              unsafe extern "C" fn(rRec: *const CRawRecordInfo) -> SyncManagerError,
-    SyncDeleteResourceRec:      unsafe extern "C" fn(rRec: CRawRecordInfo) -> SyncManagerError,
+    SyncDeleteResourceRec:      unsafe extern "C" fn(rRec: *mut CRawRecordInfo) -> SyncManagerError,
     SyncDeleteAllResourceRec:   unsafe extern "C" fn(fHandle: openDatabaseHandle) -> SyncManagerError,
     SyncReadRecordById:         unsafe extern "C" fn(rRec: *mut CRawRecordInfo) -> SyncManagerError,
     SyncReadRecordByIndex:      unsafe extern "C" fn(rRec: *mut CRawRecordInfo) -> SyncManagerError,
-    SyncReadResRecordByIndex:   unsafe extern "C" fn(rRec: *mut CRawRecordInfo, bBody: bool) -> SyncManagerError,
+    SyncReadResRecordByIndex:   unsafe extern "C" fn(rRec: *mut CRawRecordInfo, bBody: c_int) -> SyncManagerError,
     SyncReadNextModifiedRec:    unsafe extern "C" fn(rRec: *mut CRawRecordInfo) -> SyncManagerError,
     SyncReadDBAppInfoBlock:     unsafe extern "C" fn(fHandle: openDatabaseHandle, rDbInfo: *mut CDbGenInfo) -> SyncManagerError,
     SyncWriteDBAppInfoBlock:    unsafe extern "C" fn(fHandle: openDatabaseHandle, rDbInfo: *const CDbGenInfo) -> SyncManagerError,
-    SyncWriteResourceRec:       unsafe extern "C" fn(rRec: CRawRecordInfo) -> SyncManagerError,
+    SyncWriteResourceRec:       unsafe extern "C" fn(rRec: *mut CRawRecordInfo) -> SyncManagerError,
     SyncRebootSystem:           unsafe extern "C" fn() -> SyncManagerError,
     SyncReadSystemInfo:         unsafe extern "C" fn(rInfo: *mut CSystemInfo) -> SyncManagerError,
     SyncReadSingleCardInfo:     unsafe extern "C" fn(rInfo: *mut CCardInfo) -> SyncManagerError,
@@ -544,4 +1149,28 @@ pub struct SyncMgrApi {
     SyncYieldCycles:            unsafe extern "C" fn(wMaxMiliSecs: u16) -> SyncManagerError,
     SyncReadAppPreference:      unsafe extern "C" fn(rInfo: *mut CRawPreferenceInfo) -> SyncManagerError,
     SyncWriteAppPreference:     unsafe extern "C" fn(rInfo: *const CRawPreferenceInfo) -> SyncManagerError,
+    // missing from Sync Manager versions older than the API version that added them
+    SyncGetAPIVersion:          Option<unsafe extern "C" fn(pdwMajor: *mut u32, pdwMinor: *mut u32) -> SyncManagerError>,
+    SyncCallRemoteModule:       Option<unsafe extern "C" fn(pParams: *mut CCallModuleParams) -> SyncManagerError>,
+    SyncReadFeature:            Option<unsafe extern "C" fn(dwFtrCreator: u32, wFtrNum: u16, pdwFtrValue: *mut u32) -> SyncManagerError>,
+    SyncGetHHOSVersion:         Option<unsafe extern "C" fn(pwRomVMinor: *mut u16) -> u16>,
+    SyncReadOpenDbInfo:         Option<unsafe extern "C" fn(rParam: *mut SyncReadOpenDbInfoParams, rInfo: *mut SyncDatabaseInfoType) -> SyncManagerError>,
+    SyncFindDbByName:           Option<unsafe extern "C" fn(rParam: *mut SyncFindDbByNameParams, rInfo: *mut SyncDatabaseInfoType) -> SyncManagerError>,
+    SyncFindDbByTypeCreator:    Option<unsafe extern "C" fn(rParam: *mut SyncFindDbByTypeCreatorParams, rInfo: *mut SyncDatabaseInfoType) -> SyncManagerError>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_write_size_limit() {
+        let mut bytes = vec![0_u8; MAX_RECORD_SIZE];
+        assert!(CDbGenInfo::new_for_writing(&mut bytes).is_ok());
+        bytes.push(0);
+        assert!(matches!(
+            CRawRecordInfo::new_for_writing(0, 0, 0, None, &mut bytes),
+            Err(ConduitError::RecordTooLarge(len)) if len == MAX_RECORD_SIZE + 1
+        ));
+    }
 }